@@ -0,0 +1,84 @@
+// ===================================================================================
+//  BSD 3-Clause License
+//
+//  Copyright (c) 2023-2024, Liam R. (zCubed3)
+//
+//  Redistribution and use in source and binary forms, with or without
+//  modification, are permitted provided that the following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this
+//     list of conditions and the following disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice,
+//     this list of conditions and the following disclaimer in the documentation
+//     and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its
+//     contributors may be used to endorse or promote products derived from
+//     this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+//  AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+//  IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+//  FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+//  DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+//  CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+//  OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+//  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ===================================================================================
+
+use std::collections::HashMap;
+use std::fs::read_to_string;
+
+use crate::asset_format::ASSEMBLY_DEFINITION_EXTENSION;
+use crate::json::JsonValue;
+use crate::meta_file::MetaFile;
+
+/// Reads the `name` of an assembly definition
+pub fn assembly_name(meta: &MetaFile) -> Option<String> {
+    let contents = read_to_string(meta.get_paths().0).ok()?;
+    let json = JsonValue::parse(&contents)?;
+
+    Some(json.get("name")?.as_str()?.to_string())
+}
+
+fn is_assembly_definition(meta: &MetaFile) -> bool {
+    meta.base_name
+        .to_lowercase()
+        .ends_with(ASSEMBLY_DEFINITION_EXTENSION)
+}
+
+/// Matches source assembly definitions to destination ones with the same assembly name
+///
+/// Returns the destination meta for each matched source GUID, assembly file names
+/// often differ between projects even when the assembly is the same
+pub fn match_assemblies(
+    src_metas: &[MetaFile],
+    dst_metas: &[MetaFile],
+) -> HashMap<String, MetaFile> {
+    let mut dst_assemblies = HashMap::<String, &MetaFile>::new();
+
+    for dst_meta in dst_metas.iter().filter(|meta| is_assembly_definition(meta)) {
+        if let Some(name) = assembly_name(dst_meta) {
+            dst_assemblies.entry(name).or_insert(dst_meta);
+        }
+    }
+
+    let mut matched = HashMap::<String, MetaFile>::new();
+
+    for src_meta in src_metas.iter().filter(|meta| is_assembly_definition(meta)) {
+        let Some(name) = assembly_name(src_meta) else {
+            continue;
+        };
+
+        if let Some(dst_meta) = dst_assemblies.get(&name) {
+            if dst_meta.guid != src_meta.guid {
+                matched.insert(src_meta.guid.clone(), (*dst_meta).clone());
+            }
+        }
+    }
+
+    matched
+}
@@ -0,0 +1,150 @@
+// ===================================================================================
+//  BSD 3-Clause License
+//
+//  Copyright (c) 2023-2024, Liam R. (zCubed3)
+//
+//  Redistribution and use in source and binary forms, with or without
+//  modification, are permitted provided that the following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this
+//     list of conditions and the following disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice,
+//     this list of conditions and the following disclaimer in the documentation
+//     and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its
+//     contributors may be used to endorse or promote products derived from
+//     this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+//  AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+//  IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+//  FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+//  DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+//  CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+//  OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+//  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ===================================================================================
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use crate::binary::is_serialized_file;
+use crate::rewrite::{
+    find_bytes, AssemblyScanner, JsonScanner, ProjectUrlScanner, ReferenceScanner, TEXT_SCANNERS,
+};
+
+/// How many bytes are read when sniffing a file's format
+const SNIFF_SIZE: usize = 64;
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Assembly definitions, these name an assembly that other assets reference
+pub const ASSEMBLY_DEFINITION_EXTENSION: &str = ".asmdef";
+
+/// Assembly definition references, these only point at an assembly definition
+const ASSEMBLY_REFERENCE_EXTENSION: &str = ".asmref";
+
+const ASSEMBLY_EXTENSIONS: &[&str] = &[ASSEMBLY_DEFINITION_EXTENSION, ASSEMBLY_REFERENCE_EXTENSION];
+
+/// UI Toolkit documents, style sheets and theme style sheets
+const UI_TOOLKIT_EXTENSIONS: &[&str] = &[".uxml", ".uss", ".tss"];
+
+/// Assets that are known to be JSON, other files are only JSON if they start with a brace
+const JSON_EXTENSIONS: &[&str] = &[".shadergraph", ".shadersubgraph", ".vfx", ".inputactions"];
+
+/// How an asset is serialized, which decides how its references are found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetFormat {
+    /// Unity's text serialization (`%YAML 1.1` with `%TAG !u!`)
+    Yaml,
+
+    /// Unity's binary serialization ("Force Binary" assets)
+    Binary,
+
+    /// JSON based assets (ShaderGraph, VFX graph, Input Actions, etc)
+    Json,
+
+    /// Assembly definitions and references, these refer to each other with `GUID:` strings
+    Assembly,
+
+    /// UI Toolkit documents and style sheets, these reference assets through URLs
+    UiToolkit,
+
+    /// Anything we don't know how to read references from, copied as is
+    Opaque,
+}
+
+impl AssetFormat {
+    /// Detects the format of a file by sniffing its contents
+    ///
+    /// Files with one of the given extensions are always treated as text, this
+    /// allows forcing conversion of assets that don't have the usual header
+    pub fn detect<P: AsRef<Path>>(path: P, forced_extensions: &[String]) -> Self {
+        let path = path.as_ref();
+
+        let Ok(mut file) = File::open(path) else {
+            return Self::Opaque;
+        };
+
+        let mut head = Vec::<u8>::with_capacity(SNIFF_SIZE);
+        let _ = file.by_ref().take(SNIFF_SIZE as u64).read_to_end(&mut head);
+
+        let text = head.strip_prefix(UTF8_BOM).unwrap_or(&head);
+
+        if text.starts_with(b"%YAML") || find_bytes(text, b"%TAG !u!", 0).is_some() {
+            return Self::Yaml;
+        }
+
+        if is_serialized_file(path) {
+            return Self::Binary;
+        }
+
+        let name = path.to_string_lossy().to_lowercase();
+
+        if ASSEMBLY_EXTENSIONS.iter().any(|ext| name.ends_with(ext)) {
+            return Self::Assembly;
+        }
+
+        if UI_TOOLKIT_EXTENSIONS.iter().any(|ext| name.ends_with(ext)) {
+            return Self::UiToolkit;
+        }
+
+        let json_text = text.trim_ascii_start();
+
+        if json_text.starts_with(b"{") || JSON_EXTENSIONS.iter().any(|ext| name.ends_with(ext)) {
+            return Self::Json;
+        }
+
+        if forced_extensions
+            .iter()
+            .any(|ext| name.ends_with(ext.as_str()))
+        {
+            return Self::Yaml;
+        }
+
+        Self::Opaque
+    }
+
+    /// Returns true if assets of this format can reference other assets
+    pub fn is_convertible(&self) -> bool {
+        *self != Self::Opaque
+    }
+
+    /// Returns the scanners that find references in text assets of this format
+    ///
+    /// Binary and opaque assets aren't scanned as text, they have no scanners
+    pub fn scanners(&self) -> &'static [&'static dyn ReferenceScanner] {
+        match self {
+            Self::Yaml => TEXT_SCANNERS,
+            Self::Json => &[&JsonScanner],
+            Self::Assembly => &[&AssemblyScanner],
+            Self::UiToolkit => &[&ProjectUrlScanner],
+            Self::Binary | Self::Opaque => &[],
+        }
+    }
+}
@@ -0,0 +1,664 @@
+// ===================================================================================
+//  BSD 3-Clause License
+//
+//  Copyright (c) 2023-2024, Liam R. (zCubed3)
+//
+//  Redistribution and use in source and binary forms, with or without
+//  modification, are permitted provided that the following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this
+//     list of conditions and the following disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice,
+//     this list of conditions and the following disclaimer in the documentation
+//     and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its
+//     contributors may be used to endorse or promote products derived from
+//     this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+//  AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+//  IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+//  FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+//  DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+//  CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+//  OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+//  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ===================================================================================
+
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::rewrite::Resolver;
+
+/// Oldest serialized file version we know how to read (Unity 3.5)
+const MIN_VERSION: u32 = 9;
+
+/// Newest serialized file version we've seen, anything higher is assumed unknown
+const MAX_VERSION: u32 = 23;
+
+/// Class ID of MonoBehaviour, types with it carry an extra script hash
+const MONO_BEHAVIOUR: i32 = 114;
+
+/// An entry of a serialized file's external references table
+#[derive(Debug, Clone)]
+pub struct ExternalReference {
+    /// The referenced asset's GUID as text
+    pub guid: String,
+
+    /// Where the 16 GUID bytes live within the file
+    pub offset: u64,
+}
+
+/// The parts of a Unity binary serialized file header we care about
+#[derive(Debug, Clone)]
+pub struct SerializedHeader {
+    pub version: u32,
+    pub file_size: u64,
+    pub big_endian: bool,
+}
+
+/// Reads primitive values while keeping track of endianness and position
+struct SerializedReader<R: Read + Seek> {
+    inner: R,
+    big_endian: bool,
+}
+
+impl<R: Read + Seek> SerializedReader<R> {
+    fn bytes<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let mut bytes = [0u8; N];
+        self.inner.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, count: i64) -> io::Result<()> {
+        self.inner.seek(SeekFrom::Current(count))?;
+        Ok(())
+    }
+
+    fn position(&mut self) -> io::Result<u64> {
+        self.inner.stream_position()
+    }
+
+    fn align(&mut self) -> io::Result<()> {
+        let position = self.position()?;
+        self.skip(((4 - position % 4) % 4) as i64)
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.bytes::<1>()?[0])
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        let bytes = self.bytes::<4>()?;
+
+        Ok(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+
+    fn i32(&mut self) -> io::Result<i32> {
+        Ok(self.u32()? as i32)
+    }
+
+    fn i16(&mut self) -> io::Result<i16> {
+        let bytes = self.bytes::<2>()?;
+
+        Ok(if self.big_endian {
+            i16::from_be_bytes(bytes)
+        } else {
+            i16::from_le_bytes(bytes)
+        })
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        let bytes = self.bytes::<8>()?;
+
+        Ok(if self.big_endian {
+            u64::from_be_bytes(bytes)
+        } else {
+            u64::from_le_bytes(bytes)
+        })
+    }
+
+    /// Reads a null terminated string
+    fn string(&mut self) -> io::Result<String> {
+        let mut bytes = Vec::<u8>::new();
+
+        loop {
+            match self.u8()? {
+                0 => break,
+                byte => bytes.push(byte),
+            }
+
+            if bytes.len() > 0xFFFF {
+                return Err(invalid("Unterminated string"));
+            }
+        }
+
+        Ok(String::from_utf8_lossy(&bytes).to_string())
+    }
+
+    fn count(&mut self) -> io::Result<usize> {
+        let count = self.i32()?;
+
+        // Sanity check so garbage doesn't make us loop for ages
+        if !(0..=0x00FF_FFFF).contains(&count) {
+            return Err(invalid("Invalid element count"));
+        }
+
+        Ok(count as usize)
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Converts 16 GUID bytes to Unity's text form
+///
+/// Unity stores GUIDs as four u32s and prints each nibble lowest first
+pub fn guid_to_string(bytes: &[u8; 16], big_endian: bool) -> String {
+    let mut text = String::with_capacity(32);
+
+    for chunk in bytes.chunks(4) {
+        let chunk: [u8; 4] = chunk.try_into().unwrap();
+
+        let data = if big_endian {
+            u32::from_be_bytes(chunk)
+        } else {
+            u32::from_le_bytes(chunk)
+        };
+
+        for nibble in 0..8 {
+            let digit = (data >> (nibble * 4)) & 0xF;
+            text.push(char::from_digit(digit, 16).unwrap());
+        }
+    }
+
+    text
+}
+
+/// Converts a text GUID back into 16 bytes, returns None if it isn't valid hex
+pub fn guid_from_string(guid: &str, big_endian: bool) -> Option<[u8; 16]> {
+    if guid.len() != 32 {
+        return None;
+    }
+
+    let mut bytes = [0u8; 16];
+
+    for (index, group) in guid.as_bytes().chunks(8).enumerate() {
+        let mut data = 0u32;
+
+        for (nibble, digit) in group.iter().enumerate() {
+            data |= (*digit as char).to_digit(16)? << (nibble * 4);
+        }
+
+        let group_bytes = if big_endian {
+            data.to_be_bytes()
+        } else {
+            data.to_le_bytes()
+        };
+
+        bytes[index * 4..index * 4 + 4].copy_from_slice(&group_bytes);
+    }
+
+    Some(bytes)
+}
+
+impl SerializedHeader {
+    /// Reads and validates a header, returns None if this isn't a serialized file
+    pub fn read<R: Read + Seek>(reader: &mut R, actual_size: u64) -> Option<Self> {
+        let mut reader = SerializedReader {
+            inner: reader,
+            big_endian: true,
+        };
+
+        let mut metadata_size = reader.u32().ok()? as u64;
+        let mut file_size = reader.u32().ok()? as u64;
+        let version = reader.u32().ok()?;
+        let mut data_offset = reader.u32().ok()? as u64;
+
+        if !(MIN_VERSION..=MAX_VERSION).contains(&version) {
+            return None;
+        }
+
+        let endianness = reader.u8().ok()?;
+        reader.skip(3).ok()?;
+
+        if version >= 22 {
+            metadata_size = reader.u32().ok()? as u64;
+            file_size = reader.u64().ok()?;
+            data_offset = reader.u64().ok()?;
+            reader.skip(8).ok()?;
+        }
+
+        // Text files will almost never pass all of these
+        if endianness > 1 || file_size != actual_size || data_offset > file_size {
+            return None;
+        }
+
+        if metadata_size == 0 || metadata_size > file_size {
+            return None;
+        }
+
+        Some(Self {
+            version,
+            file_size,
+            big_endian: endianness == 1,
+        })
+    }
+}
+
+/// Returns true if the file at the given path is a Unity binary serialized file
+pub fn is_serialized_file<P: AsRef<Path>>(path: P) -> bool {
+    let Ok(file) = File::open(path) else {
+        return false;
+    };
+
+    let size = file.metadata().map(|m| m.len()).unwrap_or_default();
+
+    SerializedHeader::read(&mut BufReader::new(file), size).is_some()
+}
+
+/// Reads the external references table of a serialized file
+pub fn read_externals<R: Read + Seek>(
+    reader: &mut R,
+    actual_size: u64,
+) -> io::Result<(SerializedHeader, Vec<ExternalReference>)> {
+    reader.seek(SeekFrom::Start(0))?;
+
+    let header =
+        SerializedHeader::read(reader, actual_size).ok_or(invalid("Not a serialized file"))?;
+
+    let version = header.version;
+
+    let mut reader = SerializedReader {
+        inner: reader,
+        big_endian: header.big_endian,
+    };
+
+    let _unity_version = reader.string()?;
+    let _target_platform = reader.i32()?;
+
+    let enable_type_tree = if version >= 13 {
+        reader.u8()? != 0
+    } else {
+        true
+    };
+
+    for _ in 0..reader.count()? {
+        skip_serialized_type(&mut reader, version, enable_type_tree)?;
+    }
+
+    let big_id_enabled = if version < 14 {
+        reader.i32()? != 0
+    } else {
+        false
+    };
+
+    for _ in 0..reader.count()? {
+        if big_id_enabled {
+            reader.skip(8)?;
+        } else if version < 14 {
+            reader.skip(4)?;
+        } else {
+            reader.align()?;
+            reader.skip(8)?;
+        }
+
+        // Byte start, byte size and type ID
+        reader.skip(if version >= 22 { 8 } else { 4 })?;
+        reader.skip(8)?;
+
+        if version < 16 {
+            reader.skip(2)?;
+        }
+
+        if version < 11 {
+            reader.skip(2)?;
+        }
+
+        if (11..17).contains(&version) {
+            reader.skip(2)?;
+        }
+
+        if version == 15 || version == 16 {
+            reader.skip(1)?;
+        }
+    }
+
+    if version >= 11 {
+        for _ in 0..reader.count()? {
+            reader.skip(4)?;
+
+            if version < 14 {
+                reader.skip(4)?;
+            } else {
+                reader.align()?;
+                reader.skip(8)?;
+            }
+        }
+    }
+
+    let mut externals = Vec::<ExternalReference>::new();
+
+    for _ in 0..reader.count()? {
+        let _empty = reader.string()?;
+
+        let offset = reader.position()?;
+        let guid = guid_to_string(&reader.bytes::<16>()?, header.big_endian);
+        let _type = reader.i32()?;
+
+        // Unity's path of the external file, this is usually empty for assets
+        let _path = reader.string()?;
+
+        externals.push(ExternalReference { guid, offset });
+    }
+
+    if reader.position()? > header.file_size {
+        return Err(invalid("External references run past the end of the file"));
+    }
+
+    Ok((header, externals))
+}
+
+fn skip_serialized_type<R: Read + Seek>(
+    reader: &mut SerializedReader<R>,
+    version: u32,
+    enable_type_tree: bool,
+) -> io::Result<()> {
+    let class_id = reader.i32()?;
+
+    if version >= 16 {
+        let _stripped = reader.u8()?;
+    }
+
+    if version >= 17 {
+        let _script_type_index = reader.i16()?;
+    }
+
+    if version >= 13 {
+        if (version < 16 && class_id < 0) || (version >= 16 && class_id == MONO_BEHAVIOUR) {
+            reader.skip(16)?;
+        }
+
+        reader.skip(16)?;
+    }
+
+    if !enable_type_tree {
+        return Ok(());
+    }
+
+    if version >= 12 || version == 10 {
+        let node_count = reader.count()? as i64;
+        let string_size = reader.count()? as i64;
+        let node_size = if version >= 19 { 32 } else { 24 };
+
+        reader.skip(node_count * node_size + string_size)?;
+    } else {
+        skip_legacy_type_tree(reader, version)?;
+    }
+
+    if version >= 21 {
+        let dependencies = reader.count()? as i64;
+        reader.skip(dependencies * 4)?;
+    }
+
+    Ok(())
+}
+
+fn skip_legacy_type_tree<R: Read + Seek>(
+    reader: &mut SerializedReader<R>,
+    version: u32,
+) -> io::Result<()> {
+    let _type = reader.string()?;
+    let _name = reader.string()?;
+
+    // Byte size, index, is array, version and meta flags
+    reader.skip(4)?;
+
+    if version != 3 {
+        reader.skip(4)?;
+    }
+
+    reader.skip(8)?;
+
+    if version != 3 {
+        reader.skip(4)?;
+    }
+
+    for _ in 0..reader.count()? {
+        skip_legacy_type_tree(reader, version)?;
+    }
+
+    Ok(())
+}
+
+/// Copies a serialized file while rewriting the GUIDs of its external references
+///
+/// GUIDs are the same size no matter what, so only the bytes of the table change
+pub fn rewrite_serialized_file<W, F>(path: &Path, mut writer: W, mut resolver: F) -> io::Result<()>
+where
+    W: Write,
+    F: Resolver,
+{
+    let file = File::open(path)?;
+    let size = file.metadata()?.len();
+
+    let mut reader = BufReader::new(file);
+    let (header, externals) = read_externals(&mut reader, size)?;
+
+    let mut patches = Vec::<(u64, [u8; 16])>::new();
+
+    for external in &externals {
+        if let Some(replacement) = resolver.resolve(&external.guid) {
+            let bytes = guid_from_string(&replacement, header.big_endian)
+                .ok_or(invalid("Replacement GUID isn't valid hex"))?;
+
+            patches.push((external.offset, bytes));
+        }
+    }
+
+    // Only the head of the file (up to the last patch) has to be held in memory
+    let head_size = patches
+        .iter()
+        .map(|(offset, _)| offset + 16)
+        .max()
+        .unwrap_or(0);
+
+    let mut head = vec![0u8; head_size as usize];
+
+    reader.seek(SeekFrom::Start(0))?;
+    reader.read_exact(&mut head)?;
+
+    for (offset, bytes) in patches {
+        head[offset as usize..offset as usize + 16].copy_from_slice(&bytes);
+    }
+
+    writer.write_all(&head)?;
+    io::copy(&mut reader, &mut writer)?;
+
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{remove_file, write};
+    use std::io::Cursor;
+
+    const GUIDS: [&str; 2] = [
+        "0123456789abcdef0123456789abcdef",
+        "5555555555555555555555555555555c",
+    ];
+
+    /// Builds a little endian serialized file with a single object and no type tree
+    fn serialized_file(version: u32, guids: &[&str]) -> Vec<u8> {
+        let header_size = if version >= 22 { 48 } else { 20 };
+
+        let mut metadata = b"2021.3.5f1\0".to_vec();
+        metadata.extend_from_slice(&19i32.to_le_bytes());
+        metadata.push(0);
+
+        // One MonoBehaviour type, with its script and type hashes
+        metadata.extend_from_slice(&1i32.to_le_bytes());
+        metadata.extend_from_slice(&MONO_BEHAVIOUR.to_le_bytes());
+        metadata.push(0);
+        metadata.extend_from_slice(&(-1i16).to_le_bytes());
+        metadata.extend_from_slice(&[0xAA; 32]);
+
+        // One object, its path ID is aligned
+        metadata.extend_from_slice(&1i32.to_le_bytes());
+
+        while !(header_size + metadata.len()).is_multiple_of(4) {
+            metadata.push(0);
+        }
+
+        metadata.extend_from_slice(&1i64.to_le_bytes());
+
+        if version >= 22 {
+            metadata.extend_from_slice(&0u64.to_le_bytes());
+        } else {
+            metadata.extend_from_slice(&0u32.to_le_bytes());
+        }
+
+        metadata.extend_from_slice(&8u32.to_le_bytes());
+        metadata.extend_from_slice(&0i32.to_le_bytes());
+
+        // No scripts
+        metadata.extend_from_slice(&0i32.to_le_bytes());
+
+        metadata.extend_from_slice(&(guids.len() as i32).to_le_bytes());
+
+        for guid in guids {
+            metadata.push(0);
+            metadata.extend_from_slice(&guid_from_string(guid, false).unwrap());
+            metadata.extend_from_slice(&0i32.to_le_bytes());
+            metadata.push(0);
+        }
+
+        let data = b"DATADATA";
+        let data_offset = (header_size + metadata.len()).div_ceil(16) * 16;
+        let file_size = data_offset + data.len();
+
+        let mut file = Vec::<u8>::new();
+
+        if version >= 22 {
+            for value in [0, 0, version, 0] {
+                file.extend_from_slice(&value.to_be_bytes());
+            }
+
+            file.extend_from_slice(&[0; 4]);
+            file.extend_from_slice(&(metadata.len() as u32).to_be_bytes());
+            file.extend_from_slice(&(file_size as u64).to_be_bytes());
+            file.extend_from_slice(&(data_offset as u64).to_be_bytes());
+            file.extend_from_slice(&[0; 8]);
+        } else {
+            let sizes = [metadata.len(), file_size, version as usize, data_offset];
+
+            for value in sizes {
+                file.extend_from_slice(&(value as u32).to_be_bytes());
+            }
+
+            file.extend_from_slice(&[0; 4]);
+        }
+
+        file.extend_from_slice(&metadata);
+        file.resize(data_offset, 0);
+        file.extend_from_slice(data);
+        file
+    }
+
+    #[test]
+    fn guid_nibbles_are_printed_lowest_first() {
+        let mut bytes = [0u8; 16];
+        bytes[..4].copy_from_slice(&[0x10, 0x32, 0x54, 0x76]);
+
+        assert!(guid_to_string(&bytes, false).starts_with("01234567"));
+
+        bytes[..4].copy_from_slice(&[0x76, 0x54, 0x32, 0x10]);
+        assert!(guid_to_string(&bytes, true).starts_with("01234567"));
+    }
+
+    #[test]
+    fn guids_survive_a_round_trip() {
+        for guid in GUIDS {
+            for big_endian in [false, true] {
+                let bytes = guid_from_string(guid, big_endian).unwrap();
+                assert_eq!(guid_to_string(&bytes, big_endian), guid);
+            }
+        }
+
+        assert!(guid_from_string("not a guid", false).is_none());
+        assert!(guid_from_string("0123456789abcdef0123456789abcdeg", false).is_none());
+    }
+
+    #[test]
+    fn externals_are_read_from_every_header_layout() {
+        for version in [19, 22] {
+            let file = serialized_file(version, &GUIDS);
+            let size = file.len() as u64;
+
+            let (header, externals) = read_externals(&mut Cursor::new(&file), size).unwrap();
+
+            assert_eq!(header.version, version);
+            assert!(!header.big_endian);
+
+            let guids: Vec<&str> = externals.iter().map(|e| e.guid.as_str()).collect();
+            assert_eq!(guids, GUIDS);
+
+            for external in &externals {
+                let offset = external.offset as usize;
+                let bytes: [u8; 16] = file[offset..offset + 16].try_into().unwrap();
+
+                assert_eq!(guid_to_string(&bytes, false), external.guid);
+            }
+        }
+    }
+
+    #[test]
+    fn text_is_not_a_serialized_file() {
+        let text = b"%YAML 1.1\n%TAG !u! tag:unity3d.com,2011:\n--- !u!1 &1\n".repeat(4);
+        let size = text.len() as u64;
+
+        assert!(SerializedHeader::read(&mut Cursor::new(&text), size).is_none());
+        assert!(read_externals(&mut Cursor::new(&text), size).is_err());
+    }
+
+    #[test]
+    fn rewriting_only_patches_the_guids() {
+        let replacement = "fedcba9876543210fedcba9876543210";
+
+        let file = serialized_file(22, &GUIDS);
+        let path =
+            std::env::temp_dir().join(format!("binary_rewrite_{}.asset", std::process::id()));
+        write(&path, &file).unwrap();
+
+        let mut rewritten = Vec::<u8>::new();
+        let result = rewrite_serialized_file(&path, &mut rewritten, |guid: &str| {
+            (guid == GUIDS[1]).then(|| replacement.to_string())
+        });
+
+        let _ = remove_file(&path);
+        result.unwrap();
+
+        assert_eq!(rewritten.len(), file.len());
+
+        let size = rewritten.len() as u64;
+        let (_, externals) = read_externals(&mut Cursor::new(&rewritten), size).unwrap();
+
+        assert_eq!(externals[0].guid, GUIDS[0]);
+        assert_eq!(externals[1].guid, replacement);
+
+        let offset = externals[1].offset as usize;
+        assert_eq!(rewritten[..offset], file[..offset]);
+        assert_eq!(rewritten[offset + 16..], file[offset + 16..]);
+    }
+}
@@ -0,0 +1,123 @@
+// ===================================================================================
+//  BSD 3-Clause License
+//
+//  Copyright (c) 2023-2024, Liam R. (zCubed3)
+//
+//  Redistribution and use in source and binary forms, with or without
+//  modification, are permitted provided that the following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this
+//     list of conditions and the following disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice,
+//     this list of conditions and the following disclaimer in the documentation
+//     and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its
+//     contributors may be used to endorse or promote products derived from
+//     this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+//  AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+//  IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+//  FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+//  DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+//  CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+//  OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+//  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ===================================================================================
+
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+use crate::hash::stable_hash_file;
+use crate::meta_file::MetaFile;
+
+/// Why two assets sharing a GUID aren't considered the same asset
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CollisionReason {
+    Name,
+    Importer,
+    Contents,
+}
+
+impl Display for CollisionReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Name => write!(f, "names"),
+            Self::Importer => write!(f, "importers"),
+            Self::Contents => write!(f, "contents"),
+        }
+    }
+}
+
+/// A source and destination asset that share a GUID but are different assets
+///
+/// This usually happens when meta files were copy-pasted between projects
+#[derive(Debug, Clone)]
+pub struct GuidCollision {
+    pub src_meta: MetaFile,
+    pub dst_meta: MetaFile,
+    pub reason: CollisionReason,
+}
+
+impl Display for GuidCollision {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?} and {:?} share the GUID {} but have different {}",
+            self.src_meta.get_paths().0,
+            self.dst_meta.get_paths().0,
+            self.src_meta.guid,
+            self.reason
+        )
+    }
+}
+
+/// Checks whether two assets with the same GUID are actually different assets
+///
+/// Names and importers are always compared, the contents are only hashed when asked to
+/// since that means reading both assets in full
+pub fn check_collision(
+    src_meta: &MetaFile,
+    dst_meta: &MetaFile,
+    compare_contents: bool,
+) -> Option<GuidCollision> {
+    let collision = |reason| {
+        Some(GuidCollision {
+            src_meta: src_meta.clone(),
+            dst_meta: dst_meta.clone(),
+            reason,
+        })
+    };
+
+    if src_meta.base_name != dst_meta.base_name {
+        return collision(CollisionReason::Name);
+    }
+
+    // Old meta files may not name an importer, those can't be compared
+    let has_importers = !src_meta.importer.is_empty() && !dst_meta.importer.is_empty();
+
+    if has_importers && src_meta.importer != dst_meta.importer {
+        return collision(CollisionReason::Importer);
+    }
+
+    if compare_contents {
+        let (src_path, dst_path) = (src_meta.get_paths().0, dst_meta.get_paths().0);
+
+        // Folders have no contents to compare
+        if Path::new(&src_path).is_file() && Path::new(&dst_path).is_file() {
+            if let (Ok(src_hash), Ok(dst_hash)) =
+                (stable_hash_file(&src_path), stable_hash_file(&dst_path))
+            {
+                if src_hash != dst_hash {
+                    return collision(CollisionReason::Contents);
+                }
+            }
+        }
+    }
+
+    None
+}
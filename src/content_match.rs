@@ -0,0 +1,179 @@
+// ===================================================================================
+//  BSD 3-Clause License
+//
+//  Copyright (c) 2023-2024, Liam R. (zCubed3)
+//
+//  Redistribution and use in source and binary forms, with or without
+//  modification, are permitted provided that the following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this
+//     list of conditions and the following disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice,
+//     this list of conditions and the following disclaimer in the documentation
+//     and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its
+//     contributors may be used to endorse or promote products derived from
+//     this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+//  AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+//  IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+//  FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+//  DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+//  CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+//  OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+//  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ===================================================================================
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+use crate::meta_file::MetaFile;
+
+/// How much of each file is held in memory while comparing them
+const COMPARE_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Matches missing source assets to byte-identical destination assets
+///
+/// Only assets whose size exists on the other side are ever hashed, and hashes are kept on
+/// the metas so the cache can skip rehashing unchanged assets. Assets with equal hashes are
+/// compared byte by byte before they're matched. Returns the destination meta for each
+/// matched source GUID.
+pub fn match_contents(
+    src_metas: &mut [MetaFile],
+    dst_metas: &mut [MetaFile],
+    missing_guids: &HashSet<String>,
+) -> HashMap<String, MetaFile> {
+    let mut dst_sized = Vec::<(&mut MetaFile, u64)>::new();
+
+    for dst_meta in dst_metas.iter_mut() {
+        if let Some((_, size)) = dst_meta.asset_stamp() {
+            dst_sized.push((dst_meta, size));
+        }
+    }
+
+    let dst_sizes: HashSet<u64> = dst_sized.iter().map(|(_, size)| *size).collect();
+
+    let mut missing = Vec::<(&mut MetaFile, u64)>::new();
+
+    for src_meta in src_metas.iter_mut() {
+        if !missing_guids.contains(&src_meta.guid) {
+            continue;
+        }
+
+        // Empty files are all identical, matching those would be meaningless
+        match src_meta.asset_stamp() {
+            Some((_, size)) if size > 0 && dst_sizes.contains(&size) => {
+                missing.push((src_meta, size))
+            }
+            _ => {}
+        }
+    }
+
+    let src_sizes: HashSet<u64> = missing.iter().map(|(_, size)| *size).collect();
+
+    // Destination metas are sorted, so the first of several identical assets always wins
+    let mut dst_contents = HashMap::<(u64, u64), &MetaFile>::new();
+
+    for (dst_meta, size) in dst_sized {
+        if !src_sizes.contains(&size) {
+            continue;
+        }
+
+        if let Some(hash) = dst_meta.content_hash() {
+            dst_contents.entry((size, hash)).or_insert(dst_meta);
+        }
+    }
+
+    let mut matched = HashMap::<String, MetaFile>::new();
+
+    for (src_meta, size) in missing {
+        let Some(hash) = src_meta.content_hash() else {
+            continue;
+        };
+
+        let Some(dst_meta) = dst_contents.get(&(size, hash)) else {
+            continue;
+        };
+
+        // The same bytes imported differently aren't the same asset
+        if !src_meta.importer.is_empty()
+            && !dst_meta.importer.is_empty()
+            && src_meta.importer != dst_meta.importer
+        {
+            continue;
+        }
+
+        // Hashes can collide, only the bytes themselves prove the assets are identical
+        if !files_equal(src_meta.get_paths().0, dst_meta.get_paths().0).unwrap_or(false) {
+            continue;
+        }
+
+        matched.insert(src_meta.guid.clone(), (*dst_meta).clone());
+    }
+
+    matched
+}
+
+/// Compares two files byte by byte, reading both in chunks
+fn files_equal<P: AsRef<Path>>(a: P, b: P) -> io::Result<bool> {
+    let mut a = BufReader::with_capacity(COMPARE_CHUNK_SIZE, File::open(a)?);
+    let mut b = BufReader::with_capacity(COMPARE_CHUNK_SIZE, File::open(b)?);
+
+    loop {
+        let a_chunk = a.fill_buf()?;
+        let b_chunk = b.fill_buf()?;
+
+        if a_chunk.is_empty() || b_chunk.is_empty() {
+            return Ok(a_chunk.is_empty() && b_chunk.is_empty());
+        }
+
+        let length = a_chunk.len().min(b_chunk.len());
+
+        if a_chunk[..length] != b_chunk[..length] {
+            return Ok(false);
+        }
+
+        a.consume(length);
+        b.consume(length);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, remove_dir_all, write};
+
+    #[test]
+    fn files_are_compared_byte_by_byte() {
+        let root = std::env::temp_dir().join(format!("content_match_{}", std::process::id()));
+        create_dir_all(&root).unwrap();
+
+        let big = vec![7u8; 3 * COMPARE_CHUNK_SIZE + 5];
+        let mut flipped = big.clone();
+        *flipped.last_mut().unwrap() = 8;
+
+        let files = [
+            ("a", &big),
+            ("b", &big),
+            ("c", &flipped),
+            ("d", &big[1..].to_vec()),
+        ];
+
+        for (name, contents) in files {
+            write(root.join(name), contents).unwrap();
+        }
+
+        let equal = |a: &str, b: &str| files_equal(root.join(a), root.join(b)).unwrap();
+        let result = (equal("a", "b"), equal("a", "c"), equal("a", "d"));
+
+        let _ = remove_dir_all(&root);
+        assert_eq!(result, (true, false, false));
+    }
+}
@@ -0,0 +1,936 @@
+// ===================================================================================
+//  BSD 3-Clause License
+//
+//  Copyright (c) 2023-2024, Liam R. (zCubed3)
+//
+//  Redistribution and use in source and binary forms, with or without
+//  modification, are permitted provided that the following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this
+//     list of conditions and the following disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice,
+//     this list of conditions and the following disclaimer in the documentation
+//     and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its
+//     contributors may be used to endorse or promote products derived from
+//     this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+//  AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+//  IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+//  FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+//  DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+//  CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+//  OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+//  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ===================================================================================
+
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fs::*;
+use std::io::{self, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread::scope;
+
+use crate::asset_format::*;
+use crate::binary::*;
+use crate::fresh_guid::*;
+use crate::meta_file::*;
+use crate::packages::*;
+use crate::provenance::*;
+use crate::relocation::*;
+use crate::remap_table::*;
+use crate::rewrite::*;
+use crate::source_root::*;
+use crate::work_queue::WorkQueue;
+
+/// An asset that gets its references rewritten and written to the output folder
+#[derive(Default, Debug)]
+pub struct AssetConversion {
+    pub path: String,
+    pub output_path: String,
+}
+
+impl PartialEq<AssetConversion> for AssetConversion {
+    fn eq(&self, other: &AssetConversion) -> bool {
+        self.path == other.path
+    }
+}
+
+/// How source assets relate to the destination project
+#[derive(Debug, Default)]
+pub struct MetaMapping {
+    /// Source GUIDs that already exist in the destination under another GUID
+    pub remapped_metas: HashMap<String, MetaFile>,
+
+    /// Source assets (keyed by GUID) that don't exist in the destination
+    pub missing_metas: HashMap<String, MetaFile>,
+
+    /// Every asset of the destination project, keyed by GUID
+    pub dst_metas: HashMap<String, MetaFile>,
+
+    /// Source GUIDs that collide with a different destination asset and are given new ones
+    pub regenerated_guids: HashSet<String>,
+
+    /// Manual overrides (keyed by source GUID), these win over everything above
+    pub overrides: HashMap<String, RemapOverride>,
+
+    /// GUIDs provided externally (ex. by the game), these are never remapped or copied
+    pub external_guids: HashSet<String>,
+
+    /// Packages of the source project, assets in these are never copied
+    pub src_packages: PackageIndex,
+
+    /// Packages of the destination project
+    pub dst_packages: PackageIndex,
+}
+
+/// Shared state of the conversion stage
+///
+/// Assets are converted by several threads at once, each missing asset is claimed
+/// exactly once so it is only ever copied and converted by a single thread
+pub struct Converter {
+    pub src_assets: String,
+    pub dst_assets: String,
+    pub export_path: String,
+
+    /// Extensions that are always converted even if they aren't detected as Unity assets
+    pub convert_extensions: Vec<String>,
+    pub relocator: Relocator,
+    pub mapping: MetaMapping,
+
+    /// Folders assets are migrated from, the source assets and any embedded packages
+    pub source_roots: Vec<SourceRoot>,
+
+    /// Whether copied assets keep their GUIDs or get new ones
+    pub guid_mode: GuidMode,
+
+    /// Metas of the seeds, these are copied even if they aren't missing
+    seed_metas: HashMap<String, MetaFile>,
+
+    /// New GUIDs handed out so far, keyed by source GUID
+    fresh_guids: Mutex<HashMap<String, String>>,
+
+    /// Source GUIDs whose override was used at least once
+    applied_overrides: Mutex<HashSet<String>>,
+
+    /// Names of source packages that referenced assets live in
+    required_packages: Mutex<BTreeSet<String>>,
+
+    /// Referenced GUIDs that aren't in either project or any package
+    unresolved_guids: Mutex<BTreeSet<String>>,
+
+    /// GUIDs that were already copied (or are being copied) by a thread
+    claimed: Mutex<HashSet<String>>,
+
+    /// Every asset that referenced each copied asset, keyed by GUID
+    referrers: Mutex<HashMap<String, Vec<(String, ReferenceSource)>>>,
+
+    /// Which GUID gets to write each output path when relocation makes them overlap
+    output_owners: HashMap<String, String>,
+}
+
+impl Converter {
+    pub fn new(
+        src_assets: String,
+        dst_assets: String,
+        export_path: String,
+        convert_extensions: Vec<String>,
+        relocator: Relocator,
+        mapping: MetaMapping,
+        guid_mode: GuidMode,
+    ) -> Self {
+        let source_roots = source_roots(&src_assets, &mapping.src_packages);
+
+        let mut converter = Self {
+            src_assets,
+            dst_assets,
+            export_path,
+            convert_extensions,
+            relocator,
+            mapping,
+            source_roots,
+            guid_mode,
+            seed_metas: HashMap::new(),
+            fresh_guids: Mutex::new(HashMap::new()),
+            applied_overrides: Mutex::new(HashSet::new()),
+            required_packages: Mutex::new(BTreeSet::new()),
+            unresolved_guids: Mutex::new(BTreeSet::new()),
+            claimed: Mutex::new(HashSet::new()),
+            referrers: Mutex::new(HashMap::new()),
+            output_owners: HashMap::new(),
+        };
+
+        // Decided up front (by source path) so the winner doesn't depend on thread timing
+        let mut missing: Vec<&MetaFile> = converter.mapping.missing_metas.values().collect();
+        missing.sort_by_key(|meta| meta.get_paths().0);
+
+        let mut output_owners = HashMap::<String, String>::new();
+
+        for meta in missing {
+            let Some(export_dir) = converter.export_dir(&meta.directory) else {
+                continue;
+            };
+
+            let (asset_dst_path, _) = meta.get_paths_stem(export_dir);
+
+            output_owners
+                .entry(asset_dst_path)
+                .or_insert_with(|| meta.guid.clone());
+        }
+
+        converter.output_owners = output_owners;
+        converter
+    }
+
+    /// Returns the source root containing a directory and the directory relative to it
+    pub fn source_root<'a>(&self, directory: &'a Path) -> Option<(&SourceRoot, &'a Path)> {
+        self.source_roots
+            .iter()
+            .find_map(|root| Some((root, root.relative(directory)?)))
+    }
+
+    /// Returns where assets from a source directory are exported to
+    ///
+    /// Returns None for directories outside of every source root
+    pub fn export_dir(&self, directory: &str) -> Option<PathBuf> {
+        let (root, relative) = self.source_root(Path::new(directory))?;
+        Some(root.export_dir(&self.export_path, &self.relocator, relative))
+    }
+
+    /// Returns the project path of a source asset within the source project
+    pub fn source_project_path(&self, meta: &MetaFile) -> Option<String> {
+        project_path_in(&self.source_roots, meta)
+    }
+
+    /// Turns a seed path into a conversion, seeds may be relative to the source assets or
+    /// project (ex. Packages/com.company.package/Prefab.prefab) or absolute
+    pub fn seed(&self, path: &str) -> Option<AssetConversion> {
+        let path = Path::new(path);
+
+        let import = if path.is_absolute() || path.starts_with(&self.src_assets) {
+            path.to_path_buf()
+        } else {
+            let in_assets = Path::new(&self.src_assets).join(path);
+
+            match project_root(&self.src_assets) {
+                Some(project) if !in_assets.exists() => project.join(path),
+                _ => in_assets,
+            }
+        };
+
+        let output_path = self.export_dir(&import.parent()?.display().to_string())?;
+
+        Some(AssetConversion {
+            path: import.display().to_string(),
+            output_path: output_path.display().to_string(),
+        })
+    }
+
+    /// Returns the path an asset will have within the destination project (ex. Assets/Icon.png)
+    pub fn project_path(&self, guid: &str) -> Option<String> {
+        if let Some(remap) = self.mapping.overrides.get(guid) {
+            let RemapTarget::Guid(target) = &remap.target else {
+                return None;
+            };
+
+            let dst_meta = self.mapping.dst_metas.get(target)?;
+            return project_relative(&self.dst_assets, dst_meta.get_paths().0);
+        }
+
+        if let Some(dst_meta) = self.mapping.remapped_metas.get(guid) {
+            return project_relative(&self.dst_assets, dst_meta.get_paths().0);
+        }
+
+        if let Some(missing_meta) = self.mapping.missing_metas.get(guid) {
+            let (root, relative) = self.source_root(Path::new(&missing_meta.directory))?;
+
+            let relative = match root.relocate {
+                true => self.relocator.relocate(relative),
+                false => relative.to_path_buf(),
+            };
+
+            return Some(root.project_path_of(&relative.join(&missing_meta.base_name)));
+        }
+
+        if let Some(dst_meta) = self.mapping.dst_metas.get(guid) {
+            return project_relative(&self.dst_assets, dst_meta.get_paths().0);
+        }
+
+        None
+    }
+
+    /// Returns the new GUID of a copied asset (if GUIDs aren't being kept)
+    pub fn fresh_guid(&self, guid: &str) -> Option<String> {
+        let regenerate = self.mapping.regenerated_guids.contains(guid);
+
+        if self.guid_mode == GuidMode::Keep && !regenerate {
+            return None;
+        }
+
+        let meta = self.source_meta(guid)?;
+
+        let mut fresh_guids = self.fresh_guids.lock().unwrap();
+
+        if let Some(fresh_guid) = fresh_guids.get(guid) {
+            return Some(fresh_guid.clone());
+        }
+
+        let relative_path = self.source_project_path(meta)?;
+
+        // Collisions are regenerated even when every other asset keeps its GUID,
+        // seeding by the old GUID keeps repeat runs consistent
+        let fresh_guid = match &self.guid_mode {
+            GuidMode::Keep => GuidMode::Seeded(guid.to_string()).generate(&relative_path)?,
+            mode => mode.generate(&relative_path)?,
+        };
+
+        fresh_guids.insert(guid.to_string(), fresh_guid.clone());
+        Some(fresh_guid)
+    }
+
+    /// Returns the overrides that were actually used, sorted by source
+    pub fn applied_overrides(&self) -> Vec<&RemapOverride> {
+        let applied = self.applied_overrides.lock().unwrap();
+
+        let mut overrides: Vec<&RemapOverride> = applied
+            .iter()
+            .filter_map(|guid| self.mapping.overrides.get(guid))
+            .collect();
+
+        overrides.sort_by(|a, b| a.source.cmp(&b.source));
+        overrides
+    }
+
+    /// Returns the source packages referenced by converted assets, sorted by name
+    pub fn required_packages(&self) -> Vec<&Package> {
+        let required = self.required_packages.lock().unwrap();
+
+        required
+            .iter()
+            .filter_map(|name| self.mapping.src_packages.find_by_name(name))
+            .collect()
+    }
+
+    /// Returns referenced GUIDs that couldn't be found anywhere, sorted
+    pub fn unresolved_guids(&self) -> Vec<String> {
+        self.unresolved_guids
+            .lock()
+            .unwrap()
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Records a reference to an asset outside of the source assets folder
+    fn note_outside_reference(&self, guid: &str) {
+        if is_builtin_guid(guid) || guid == NULL_GUID || self.mapping.dst_metas.contains_key(guid) {
+            return;
+        }
+
+        // Embedded packages are migrated along with the assets, they aren't required
+        if let Some(package) = self.mapping.src_packages.find(guid) {
+            if !package.embedded {
+                self.required_packages
+                    .lock()
+                    .unwrap()
+                    .insert(package.name.clone());
+            }
+
+            return;
+        }
+
+        if self.mapping.dst_packages.find(guid).is_some() {
+            return;
+        }
+
+        self.unresolved_guids
+            .lock()
+            .unwrap()
+            .insert(guid.to_string());
+    }
+
+    /// Returns how many copied assets were given a new GUID
+    pub fn fresh_guid_count(&self) -> usize {
+        self.fresh_guids.lock().unwrap().len()
+    }
+
+    /// Claims a GUID, returns false if another thread already claimed it
+    pub fn claim(&self, guid: &str) -> bool {
+        self.claimed.lock().unwrap().insert(guid.to_string())
+    }
+
+    /// Returns the source meta file of a copied asset or seed
+    fn source_meta(&self, guid: &str) -> Option<&MetaFile> {
+        self.mapping
+            .missing_metas
+            .get(guid)
+            .or_else(|| self.seed_metas.get(guid))
+    }
+
+    /// Picks one referrer for each copied asset, the one with the shortest chain from a
+    /// seed and then the lowest path, so reports don't depend on thread timing
+    fn chosen_referrers(&self) -> HashMap<String, (String, ReferenceSource)> {
+        let referrers = self.referrers.lock().unwrap();
+
+        let mut references = HashMap::<&str, Vec<&str>>::new();
+
+        for (guid, candidates) in referrers.iter() {
+            for (referrer, _) in candidates {
+                references.entry(referrer).or_default().push(guid);
+            }
+        }
+
+        // Breadth first from the seeds, so each depth is the shortest chain to an asset
+        let mut depths = HashMap::<&str, usize>::new();
+        let mut pending = VecDeque::<&str>::new();
+
+        for seed in self.seed_metas.keys() {
+            depths.insert(seed, 0);
+            pending.push_back(seed);
+        }
+
+        while let Some(guid) = pending.pop_front() {
+            let depth = depths[guid] + 1;
+
+            for referenced in references.get(guid).into_iter().flatten() {
+                if !depths.contains_key(referenced) {
+                    depths.insert(referenced, depth);
+                    pending.push_back(referenced);
+                }
+            }
+        }
+
+        referrers
+            .iter()
+            .filter_map(|(guid, candidates)| {
+                let (_, referrer, source) = candidates
+                    .iter()
+                    .filter_map(|(referrer, source)| {
+                        let depth = *depths.get(referrer.as_str())?;
+                        let path = self.source_project_path(self.source_meta(referrer)?)?;
+
+                        Some(((depth, path), referrer, source))
+                    })
+                    .min_by(|a, b| a.0.cmp(&b.0))?;
+
+                Some((guid.clone(), (referrer.clone(), *source)))
+            })
+            .collect()
+    }
+
+    /// Returns why each seed and copied asset was migrated, sorted by path
+    pub fn provenance(&self) -> ProvenanceReport {
+        let referrers = self.chosen_referrers();
+
+        let mut entries: Vec<Provenance> = self
+            .claimed
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|guid| {
+                let meta = self.source_meta(guid)?;
+                let referrer = referrers.get(guid);
+
+                let field = referrer.and_then(|(referrer, source)| {
+                    let (referrer_path, _) = self.source_meta(referrer)?.get_paths();
+                    Some(describe_reference(Path::new(&referrer_path), guid, *source))
+                });
+
+                Some(Provenance {
+                    guid: guid.clone(),
+                    path: self.source_project_path(meta)?,
+                    referrer: referrer.map(|(referrer, _)| referrer.clone()),
+                    field,
+                })
+            })
+            .collect();
+
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        ProvenanceReport { entries }
+    }
+
+    /// Converts the given seeds and everything they depend on
+    pub fn run(&mut self, seeds: Vec<AssetConversion>, thread_count: usize) {
+        // Seeds are claimed first so references to them don't convert them twice
+        for seed in &seeds {
+            if let Ok(meta) =
+                MetaFile::read_from_path(&PathBuf::from(format!("{}.meta", seed.path)))
+            {
+                self.claim(&meta.guid);
+                self.seed_metas.insert(meta.guid.clone(), meta);
+            }
+        }
+
+        let converter = &*self;
+        let queue = WorkQueue::new(thread_count, seeds);
+
+        scope(|scope| {
+            for worker in 0..queue.worker_count() {
+                let queue = &queue;
+
+                scope.spawn(move || {
+                    while let Some(convert) = queue.pop(worker) {
+                        let _finishing = queue.finishing();
+                        converter.convert(worker, convert, queue);
+                    }
+                });
+            }
+        });
+
+        // Exported packages need their package.json for Unity to recognise them
+        for root in &self.source_roots {
+            let package_json = root.path.join("package.json");
+            let output = Path::new(&self.export_path).join(&root.output);
+
+            if package_json.is_file() && output.is_dir() {
+                if let Err(error) = copy(&package_json, output.join("package.json")) {
+                    println!(
+                        "[Conversion]: Failed to copy {:?} ({})",
+                        package_json, error
+                    );
+                }
+            }
+        }
+    }
+
+    fn convert(&self, worker: usize, convert: AssetConversion, queue: &WorkQueue<AssetConversion>) {
+        let prefab_path = Path::new(&convert.path);
+
+        let meta_path = meta_path_of(prefab_path);
+
+        // The asset's own GUID, references found while converting it are attributed to it
+        let guid = MetaFile::read_from_path(&meta_path)
+            .ok()
+            .map(|meta| meta.guid);
+
+        let _ = create_dir_all(&convert.output_path);
+
+        let mut file_path = PathBuf::from(convert.output_path);
+        file_path.push(prefab_path.file_name().unwrap());
+
+        let result = if prefab_path.is_dir() {
+            // Folders only have a meta file
+            create_dir_all(&file_path)
+        } else {
+            self.convert_asset(worker, guid.as_deref(), prefab_path, &file_path, queue)
+        };
+
+        // A meta without its asset would only confuse Unity
+        if let Err(error) = result {
+            println!(
+                "[Conversion]: Failed to convert {:?}, skipping it ({})",
+                prefab_path, error
+            );
+
+            return;
+        }
+
+        // Importers keep references too (ex. material remaps of models), so metas are converted
+        if meta_path.exists() {
+            if let Err(error) = self.convert_meta(
+                worker,
+                guid.as_deref(),
+                &meta_path,
+                &meta_path_of(&file_path),
+                queue,
+            ) {
+                println!(
+                    "[Conversion]: Failed to convert {:?} ({})",
+                    meta_path, error
+                );
+            }
+        }
+    }
+
+    fn convert_asset(
+        &self,
+        worker: usize,
+        guid: Option<&str>,
+        src_path: &Path,
+        dst_path: &Path,
+        queue: &WorkQueue<AssetConversion>,
+    ) -> io::Result<()> {
+        let mut format = AssetFormat::detect(src_path, &self.convert_extensions);
+
+        // Assets that can't reference anything are never going to change, don't copy them twice
+        if !format.is_convertible() && dst_path.exists() {
+            return Ok(());
+        }
+
+        let mut src_file = File::open(src_path)?;
+
+        // Detection only looks at the header, anything that turns out not to parse is copied
+        if format == AssetFormat::Binary {
+            let size = src_file.metadata()?.len();
+
+            if let Err(error) = read_externals(&mut BufReader::new(&src_file), size) {
+                println!(
+                    "[Conversion]: {:?} isn't a serialized file we can read, copying it as-is ({})",
+                    src_path, error
+                );
+
+                format = AssetFormat::Opaque;
+            }
+
+            src_file.seek(SeekFrom::Start(0))?;
+        }
+
+        let resolve = ConversionResolver {
+            converter: self,
+            worker,
+            queue,
+            referrer: guid,
+            source: ReferenceSource::Asset(format),
+            own_guid: None,
+        };
+
+        // Files are streamed through so even huge scenes don't need to fit in memory
+        write_replacing(dst_path, |dst_file| match format {
+            AssetFormat::Binary => {
+                rewrite_serialized_file(src_path, BufWriter::new(dst_file), resolve)
+            }
+            AssetFormat::Opaque => {
+                let mut writer = BufWriter::new(dst_file);
+                io::copy(&mut BufReader::new(src_file), &mut writer)?;
+                writer.flush()
+            }
+            _ => rewrite_stream(
+                format.scanners(),
+                BufReader::new(src_file),
+                BufWriter::new(dst_file),
+                resolve,
+            ),
+        })
+    }
+
+    fn convert_meta(
+        &self,
+        worker: usize,
+        guid: Option<&str>,
+        src_path: &Path,
+        dst_path: &Path,
+        queue: &WorkQueue<AssetConversion>,
+    ) -> io::Result<()> {
+        let src_file = File::open(src_path)?;
+
+        write_replacing(dst_path, |dst_file| {
+            rewrite_stream(
+                &[&YamlScanner],
+                BufReader::new(src_file),
+                BufWriter::new(dst_file),
+                ConversionResolver {
+                    converter: self,
+                    worker,
+                    queue,
+                    referrer: guid,
+                    source: ReferenceSource::Meta,
+                    own_guid: guid,
+                },
+            )
+        })
+    }
+
+    /// Decides what happens to a referenced GUID, returns its replacement (if any)
+    fn resolve(
+        &self,
+        worker: usize,
+        referrer: Option<(&str, ReferenceSource)>,
+        guid: &str,
+        queue: &WorkQueue<AssetConversion>,
+    ) -> Option<String> {
+        // Manual overrides win over any matching
+        if let Some(remap) = self.mapping.overrides.get(guid) {
+            self.applied_overrides
+                .lock()
+                .unwrap()
+                .insert(guid.to_string());
+
+            return match &remap.target {
+                RemapTarget::Guid(target) => Some(target.clone()),
+                RemapTarget::Keep => None,
+                RemapTarget::Null => Some(NULL_GUID.to_string()),
+            };
+        }
+
+        // Externally provided assets are referenced as-is
+        if self.mapping.external_guids.contains(guid) {
+            return None;
+        }
+
+        // Check if this has been remapped
+        if let Some(meta_file) = self.mapping.remapped_metas.get(guid) {
+            return Some(meta_file.guid.clone());
+        }
+
+        // Check if this is in our list of missing ones
+        // If so (and nobody else got to it first) copy it
+        if let Some(missing_meta) = self.mapping.missing_metas.get(guid) {
+            // Every referrer is kept, one is picked once the whole run is done
+            if let Some((referrer, source)) = referrer {
+                let mut referrers = self.referrers.lock().unwrap();
+                let candidates = referrers.entry(guid.to_string()).or_default();
+
+                if !candidates.iter().any(|(known, _)| known == referrer) {
+                    candidates.push((referrer.to_string(), source));
+                }
+            }
+
+            if self.claim(guid) {
+                self.copy_missing(worker, missing_meta, queue);
+            }
+
+            return self.fresh_guid(guid);
+        }
+
+        if self.seed_metas.contains_key(guid) {
+            return self.fresh_guid(guid);
+        }
+
+        // Built-in resources and package assets are referenced as-is
+        self.note_outside_reference(guid);
+        None
+    }
+
+    fn copy_missing(
+        &self,
+        worker: usize,
+        missing_meta: &MetaFile,
+        queue: &WorkQueue<AssetConversion>,
+    ) {
+        let (asset_src_path, _) = missing_meta.get_paths();
+
+        let Some(export_dir) = self.export_dir(&missing_meta.directory) else {
+            println!(
+                "[Conversion]: Skipping {:?}, it's outside of every source root",
+                asset_src_path
+            );
+
+            return;
+        };
+
+        let export_path = export_dir.display().to_string();
+        let (asset_dst_path, _) = missing_meta.get_paths_stem(&export_path);
+
+        if self.output_owners.get(&asset_dst_path) != Some(&missing_meta.guid) {
+            println!(
+                "[Conversion]: Skipping {:?}, another asset was relocated to {:?}",
+                asset_src_path, asset_dst_path
+            );
+
+            return;
+        }
+
+        // Every copied asset is queued, even if just for its meta file
+        println!(
+            "[Conversion]: Enqueuing referenced asset {:?}",
+            asset_src_path
+        );
+
+        queue.push(
+            worker,
+            AssetConversion {
+                path: asset_src_path,
+                output_path: export_path,
+            },
+        );
+    }
+}
+
+/// Resolves the references of a single asset that is being converted
+struct ConversionResolver<'a> {
+    converter: &'a Converter,
+    worker: usize,
+    queue: &'a WorkQueue<AssetConversion>,
+
+    /// The GUID of the asset being converted, assets it pulls in are attributed to it
+    referrer: Option<&'a str>,
+
+    /// Whether the asset or its meta file is being converted, and how it's serialized
+    source: ReferenceSource,
+
+    /// The GUID of the asset itself (when converting meta files), this isn't a reference
+    /// and only changes when copied assets are given new GUIDs
+    own_guid: Option<&'a str>,
+}
+
+impl Resolver for ConversionResolver<'_> {
+    fn resolve(&mut self, guid: &str) -> Option<String> {
+        if self.own_guid == Some(guid) {
+            return self.converter.fresh_guid(guid);
+        }
+
+        let referrer = self.referrer.map(|referrer| (referrer, self.source));
+
+        self.converter
+            .resolve(self.worker, referrer, guid, self.queue)
+    }
+
+    fn project_path(&mut self, guid: &str) -> Option<String> {
+        self.converter.project_path(guid)
+    }
+}
+
+/// Returns the path of an asset's meta file, folders and extensionless files included
+fn meta_path_of(path: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.meta", path.display()))
+}
+
+/// Writes a file through a temporary one that only replaces it once writing succeeded, so a
+/// failed conversion never leaves a partial file behind for Unity to import
+fn write_replacing<F>(path: &Path, write: F) -> io::Result<()>
+where
+    F: FnOnce(File) -> io::Result<()>,
+{
+    let partial = PathBuf::from(format!("{}.partial", path.display()));
+
+    match File::create(&partial).and_then(write) {
+        Ok(()) => rename(&partial, path),
+        Err(error) => {
+            let _ = remove_file(&partial);
+            Err(error)
+        }
+    }
+}
+
+/// Turns a path within an assets folder into a project path (ex. Assets/Icon.png)
+pub fn project_relative<P: AsRef<Path>>(assets: &str, path: P) -> Option<String> {
+    let relative = path.as_ref().strip_prefix(assets).ok()?;
+
+    let mut project_path = String::from("Assets");
+
+    for component in relative.components() {
+        project_path.push('/');
+        project_path.push_str(&component.as_os_str().to_string_lossy());
+    }
+
+    Some(project_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn meta_paths_append_to_the_whole_name() {
+        let cases = [
+            ("Assets/Icon.png", "Assets/Icon.png.meta"),
+            ("Assets/Prefabs", "Assets/Prefabs.meta"),
+            ("Assets/Docs/LICENSE", "Assets/Docs/LICENSE.meta"),
+            (
+                "Assets/Shaders/Glow.shadergraph",
+                "Assets/Shaders/Glow.shadergraph.meta",
+            ),
+        ];
+
+        for (path, meta_path) in cases {
+            assert_eq!(meta_path_of(Path::new(path)), PathBuf::from(meta_path));
+        }
+    }
+
+    #[test]
+    fn folders_and_extensionless_files_keep_their_metas() {
+        let root = std::env::temp_dir().join(format!("conversion_metas_{}", std::process::id()));
+        let src_assets = root.join("src").join("Assets");
+        let export_path = root.join("out");
+
+        create_dir_all(src_assets.join("Folder")).unwrap();
+
+        write(
+            src_assets.join("Folder.meta"),
+            "fileFormatVersion: 2\nguid: 0123456789abcdef0123456789abcde1\nfolderAsset: yes\n",
+        )
+        .unwrap();
+        write(src_assets.join("LICENSE"), "MIT\n").unwrap();
+        write(
+            src_assets.join("LICENSE.meta"),
+            "fileFormatVersion: 2\nguid: 0123456789abcdef0123456789abcde2\nDefaultImporter:\n",
+        )
+        .unwrap();
+
+        let mut converter = Converter::new(
+            src_assets.display().to_string(),
+            root.join("dst").join("Assets").display().to_string(),
+            export_path.display().to_string(),
+            Vec::new(),
+            Relocator::default(),
+            MetaMapping::default(),
+            GuidMode::Keep,
+        );
+
+        let seeds = ["Folder", "LICENSE"]
+            .iter()
+            .map(|seed| converter.seed(seed).unwrap())
+            .collect();
+
+        converter.run(seeds, 2);
+
+        let exported = |name: &str| export_path.join(name).exists();
+
+        let result = (
+            exported("Folder"),
+            exported("Folder.meta"),
+            exported("LICENSE"),
+            exported("LICENSE.meta"),
+            exported("Folder..meta") || exported("LICENSE..meta"),
+        );
+
+        let _ = remove_dir_all(&root);
+        assert_eq!(result, (true, true, true, true, false));
+    }
+
+    #[test]
+    fn unreadable_serialized_files_are_copied_as_is() {
+        let root = std::env::temp_dir().join(format!("conversion_binary_{}", std::process::id()));
+        let src_assets = root.join("src").join("Assets");
+        let export_path = root.join("out");
+
+        create_dir_all(&src_assets).unwrap();
+
+        // A valid header followed by a Unity version that never ends
+        let mut contents = Vec::<u8>::new();
+
+        for field in [16u32, 64, 17, 32] {
+            contents.extend_from_slice(&field.to_be_bytes());
+        }
+
+        contents.extend_from_slice(&[0; 4]);
+        contents.resize(64, b'x');
+
+        write(src_assets.join("Broken.asset"), &contents).unwrap();
+        write(
+            src_assets.join("Broken.asset.meta"),
+            "fileFormatVersion: 2\nguid: 0123456789abcdef0123456789abcde3\nNativeFormatImporter:\n",
+        )
+        .unwrap();
+
+        let mut converter = Converter::new(
+            src_assets.display().to_string(),
+            root.join("dst").join("Assets").display().to_string(),
+            export_path.display().to_string(),
+            Vec::new(),
+            Relocator::default(),
+            MetaMapping::default(),
+            GuidMode::Keep,
+        );
+
+        assert_eq!(
+            AssetFormat::detect(src_assets.join("Broken.asset"), &[]),
+            AssetFormat::Binary
+        );
+
+        let seed = converter.seed("Broken.asset").unwrap();
+        converter.run(vec![seed], 1);
+
+        let exported = read(export_path.join("Broken.asset")).ok();
+        let meta_exported = export_path.join("Broken.asset.meta").exists();
+
+        let _ = remove_dir_all(&root);
+        assert_eq!(exported, Some(contents));
+        assert!(meta_exported);
+    }
+}
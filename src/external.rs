@@ -0,0 +1,113 @@
+// ===================================================================================
+//  BSD 3-Clause License
+//
+//  Copyright (c) 2023-2024, Liam R. (zCubed3)
+//
+//  Redistribution and use in source and binary forms, with or without
+//  modification, are permitted provided that the following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this
+//     list of conditions and the following disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice,
+//     this list of conditions and the following disclaimer in the documentation
+//     and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its
+//     contributors may be used to endorse or promote products derived from
+//     this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+//  AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+//  IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+//  FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+//  DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+//  CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+//  OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+//  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ===================================================================================
+
+use std::collections::HashSet;
+use std::fs::read_to_string;
+use std::path::{Component, Path};
+
+use crate::meta_file::MetaFile;
+use crate::relocation::assets_relative_parts;
+use crate::rewrite::is_guid;
+
+/// Assets supplied at runtime (ex. SDK scripts or shaders bundled with the game)
+///
+/// References to these are kept untouched, they are never copied or reported as missing
+#[derive(Debug, Default)]
+pub struct ExternalAssets {
+    pub guids: HashSet<String>,
+
+    /// Lowercase path components (relative to the assets folder) of files and folders
+    pub paths: Vec<Vec<String>>,
+}
+
+impl ExternalAssets {
+    /// Loads external GUIDs and paths, one per line
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Self {
+        let mut external = Self::default();
+
+        if let Ok(file) = read_to_string(path) {
+            for line in file.lines() {
+                let line = line.trim();
+
+                if line.starts_with('#') || line.is_empty() {
+                    continue;
+                }
+
+                if is_guid(line.as_bytes()) {
+                    external.guids.insert(line.to_lowercase());
+                    continue;
+                }
+
+                let parts: Vec<String> = assets_relative_parts(line)
+                    .iter()
+                    .map(|part| part.to_lowercase())
+                    .collect();
+
+                if !parts.is_empty() {
+                    external.paths.push(parts);
+                }
+            }
+        }
+
+        external
+    }
+
+    /// Returns whether a source asset is provided externally, by GUID or by path
+    pub fn contains(&self, assets: &str, meta: &MetaFile) -> bool {
+        if self.guids.contains(&meta.guid) {
+            return true;
+        }
+
+        let asset_path = meta.get_paths().0;
+
+        let Ok(relative) = Path::new(&asset_path).strip_prefix(assets) else {
+            return false;
+        };
+
+        let parts: Vec<String> = relative
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(part) => Some(part.to_string_lossy().to_lowercase()),
+                _ => None,
+            })
+            .collect();
+
+        self.paths.iter().any(|path| parts.starts_with(path))
+    }
+
+    pub fn len(&self) -> usize {
+        self.guids.len() + self.paths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
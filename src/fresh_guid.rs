@@ -0,0 +1,87 @@
+// ===================================================================================
+//  BSD 3-Clause License
+//
+//  Copyright (c) 2023-2024, Liam R. (zCubed3)
+//
+//  Redistribution and use in source and binary forms, with or without
+//  modification, are permitted provided that the following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this
+//     list of conditions and the following disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice,
+//     this list of conditions and the following disclaimer in the documentation
+//     and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its
+//     contributors may be used to endorse or promote products derived from
+//     this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+//  AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+//  IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+//  FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+//  DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+//  CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+//  OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+//  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ===================================================================================
+
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+
+use crate::hash::stable_hash;
+
+/// Decides which GUIDs copied assets end up with
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum GuidMode {
+    /// Copied assets keep their source GUIDs
+    #[default]
+    Keep,
+
+    /// Every copied asset gets a new random GUID
+    Random,
+
+    /// New GUIDs are derived from the asset's path and a seed, repeat runs produce the same GUIDs
+    Seeded(String),
+}
+
+impl GuidMode {
+    /// Parses the value of `--fresh-guids`, anything other than "random" is used as a seed
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "random" => Self::Random,
+            seed => Self::Seeded(seed.to_string()),
+        }
+    }
+
+    /// Creates a new GUID for the asset at the given (assets relative) path
+    pub fn generate(&self, relative_path: &str) -> Option<String> {
+        let (high, low) = match self {
+            Self::Keep => return None,
+            Self::Random => {
+                // Each RandomState is keyed differently, so these never repeat
+                (
+                    RandomState::new().hash_one(relative_path),
+                    RandomState::new().hash_one(relative_path),
+                )
+            }
+            Self::Seeded(seed) => (
+                mix(stable_hash(format!("{}\n{}\n0", seed, relative_path))),
+                mix(stable_hash(format!("{}\n{}\n1", seed, relative_path))),
+            ),
+        };
+
+        Some(format!("{:016x}{:016x}", high, low))
+    }
+}
+
+/// Spreads the bits of a hash out (splitmix64 finalizer), FNV alone leaves similar inputs
+/// with similar hashes
+fn mix(mut value: u64) -> u64 {
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+    value ^ (value >> 31)
+}
@@ -0,0 +1,227 @@
+// ===================================================================================
+//  BSD 3-Clause License
+//
+//  Copyright (c) 2023-2024, Liam R. (zCubed3)
+//
+//  Redistribution and use in source and binary forms, with or without
+//  modification, are permitted provided that the following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this
+//     list of conditions and the following disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice,
+//     this list of conditions and the following disclaimer in the documentation
+//     and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its
+//     contributors may be used to endorse or promote products derived from
+//     this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+//  AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+//  IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+//  FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+//  DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+//  CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+//  OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+//  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ===================================================================================
+
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::Path;
+
+use crate::meta_file::MetaFile;
+
+/// Groups of extensions that are interchangeable when fuzzy matching (ex. .psd and .png)
+#[derive(Debug, Default)]
+pub struct ExtensionClasses {
+    classes: Vec<Vec<String>>,
+}
+
+impl ExtensionClasses {
+    /// Loads extension classes, one class of whitespace separated extensions per line
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Self {
+        let mut extension_classes = Self::default();
+
+        if let Ok(file) = read_to_string(path) {
+            for line in file.lines() {
+                let line = line.trim();
+
+                if line.starts_with('#') || line.is_empty() {
+                    continue;
+                }
+
+                extension_classes.classes.push(
+                    line.split_whitespace()
+                        .map(|extension| extension.trim_start_matches('.').to_lowercase())
+                        .collect(),
+                );
+            }
+        }
+
+        extension_classes
+    }
+
+    /// Returns whether two (lowercase) extensions are the same or in the same class
+    pub fn equivalent(&self, a: &str, b: &str) -> bool {
+        a == b
+            || self.classes.iter().any(|class| {
+                class.iter().any(|extension| extension == a)
+                    && class.iter().any(|extension| extension == b)
+            })
+    }
+}
+
+/// A destination asset found by fuzzy matching, these are always reported
+#[derive(Debug, Clone)]
+pub struct FuzzyMatch {
+    pub dst_meta: MetaFile,
+
+    /// Edit distance between both (lowercase) file stems
+    pub distance: usize,
+}
+
+/// Finds destination assets with a similar name, only used when nothing matches exactly
+///
+/// Names are compared without casing or extension (as long as both extensions are in the
+/// same class) and may differ by a few characters, but the importers must always match.
+/// Numbers must match too, numbered variants (ex. Rock_01 and Rock_02) aren't typos
+pub struct FuzzyMatcher<'a> {
+    classes: ExtensionClasses,
+    max_distance: usize,
+    by_importer: HashMap<&'a str, Vec<(String, String, &'a MetaFile)>>,
+}
+
+impl<'a> FuzzyMatcher<'a> {
+    pub fn new(classes: ExtensionClasses, max_distance: usize, dst_metas: &'a [MetaFile]) -> Self {
+        let mut by_importer = HashMap::<&str, Vec<(String, String, &MetaFile)>>::new();
+
+        // Without an importer there is nothing to constrain the match with
+        for dst_meta in dst_metas.iter().filter(|meta| !meta.importer.is_empty()) {
+            let (stem, extension) = split_name(&dst_meta.base_name);
+
+            by_importer
+                .entry(&dst_meta.importer)
+                .or_default()
+                .push((stem, extension, dst_meta));
+        }
+
+        Self {
+            classes,
+            max_distance,
+            by_importer,
+        }
+    }
+
+    /// Returns the closest destination asset, ties go to the same extension and then
+    /// whichever was collected first
+    pub fn find(&self, src_meta: &MetaFile) -> Option<FuzzyMatch> {
+        let candidates = self.by_importer.get(src_meta.importer.as_str())?;
+        let (src_stem, src_extension) = split_name(&src_meta.base_name);
+        let src_numbers = numbers(&src_stem);
+
+        // Folders share an importer with plain files, neither should match the other
+        let src_is_file = src_meta.asset_stamp().is_some();
+
+        let mut best: Option<(usize, bool, &MetaFile)> = None;
+
+        for (dst_stem, dst_extension, dst_meta) in candidates {
+            if !self.classes.equivalent(&src_extension, dst_extension) {
+                continue;
+            }
+
+            let Some(distance) = edit_distance(&src_stem, dst_stem, self.max_distance) else {
+                continue;
+            };
+
+            if numbers(dst_stem) != src_numbers {
+                continue;
+            }
+
+            let different_extension = src_extension != *dst_extension;
+
+            if best.is_some_and(|best| (best.0, best.1) <= (distance, different_extension)) {
+                continue;
+            }
+
+            if dst_meta.asset_stamp().is_some() != src_is_file {
+                continue;
+            }
+
+            best = Some((distance, different_extension, dst_meta));
+        }
+
+        best.map(|(distance, _, dst_meta)| FuzzyMatch {
+            dst_meta: dst_meta.clone(),
+            distance,
+        })
+    }
+}
+
+/// Splits a file name into its lowercase stem and extension
+fn split_name(name: &str) -> (String, String) {
+    let name = name.to_lowercase();
+
+    match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem.to_string(), extension.to_string()),
+        _ => (name, String::new()),
+    }
+}
+
+/// Returns every run of digits in a name, in order
+fn numbers(name: &str) -> Vec<&str> {
+    name.split(|c: char| !c.is_ascii_digit())
+        .filter(|run| !run.is_empty())
+        .collect()
+}
+
+/// Levenshtein distance between two strings, None once it exceeds the maximum
+fn edit_distance(a: &str, b: &str, max_distance: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    if a.len().abs_diff(b.len()) > max_distance {
+        return None;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.iter().enumerate() {
+        current[0] = i + 1;
+
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+
+        // Every later row is at least as large as the smallest value of this one
+        if current.iter().min().is_some_and(|min| *min > max_distance) {
+            return None;
+        }
+
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    Some(previous[b.len()]).filter(|distance| *distance <= max_distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbered_variants_are_not_typos() {
+        let (rock_01, rock_02) = ("rock_01", "rock_02");
+
+        assert_eq!(edit_distance(rock_01, rock_02, 2), Some(1));
+        assert_ne!(numbers(rock_01), numbers(rock_02));
+
+        assert_eq!(numbers("rock01"), numbers(rock_01));
+        assert_eq!(numbers("lod2_mesh_10"), vec!["2", "10"]);
+        assert!(numbers("rock").is_empty());
+    }
+}
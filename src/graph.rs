@@ -0,0 +1,444 @@
+// ===================================================================================
+//  BSD 3-Clause License
+//
+//  Copyright (c) 2023-2024, Liam R. (zCubed3)
+//
+//  Redistribution and use in source and binary forms, with or without
+//  modification, are permitted provided that the following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this
+//     list of conditions and the following disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice,
+//     this list of conditions and the following disclaimer in the documentation
+//     and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its
+//     contributors may be used to endorse or promote products derived from
+//     this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+//  AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+//  IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+//  FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+//  DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+//  CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+//  OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+//  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ===================================================================================
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs::{write, File};
+use std::io;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread::scope;
+
+use crate::asset_format::AssetFormat;
+use crate::binary::read_externals;
+use crate::json::JsonValue;
+use crate::meta_file::MetaFile;
+use crate::packages::{is_builtin_guid, project_root, PackageIndex};
+use crate::rewrite::{is_guid, rewrite_stream, YamlScanner};
+use crate::source_root::{project_path_in, SourceRoot};
+use crate::work_queue::WorkQueue;
+
+/// File formats the dependency graph can be exported as
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GraphFormat {
+    Dot,
+    GraphMl,
+    Json,
+}
+
+impl GraphFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "dot" => Some(Self::Dot),
+            "graphml" => Some(Self::GraphMl),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Dot => "dot",
+            Self::GraphMl => "graphml",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// Where a referenced GUID lives
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeKind {
+    /// An asset of the project (or one of its embedded packages)
+    Asset,
+
+    /// An asset of an installed package
+    Package,
+
+    /// One of Unity's built-in resources
+    Builtin,
+
+    /// Nothing with this GUID was found
+    Missing,
+}
+
+impl NodeKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Asset => "asset",
+            Self::Package => "package",
+            Self::Builtin => "builtin",
+            Self::Missing => "missing",
+        }
+    }
+}
+
+/// A single asset of the dependency graph
+#[derive(Debug, Clone)]
+pub struct GraphNode {
+    pub guid: String,
+    pub kind: NodeKind,
+
+    /// The project path (ex. Assets/Icon.png), only known for project assets
+    pub path: Option<String>,
+
+    /// The importer of the asset (ex. TextureImporter)
+    pub importer: String,
+
+    /// How the asset is serialized, only known for project assets
+    pub format: Option<AssetFormat>,
+
+    /// The package containing the asset, if it's in one
+    pub package: Option<String>,
+}
+
+/// Every asset reachable from a set of seeds and the references between them
+#[derive(Debug, Default)]
+pub struct DependencyGraph {
+    pub nodes: BTreeMap<String, GraphNode>,
+
+    /// References as (from, to) GUID pairs
+    pub edges: BTreeSet<(String, String)>,
+}
+
+/// Returns every GUID an asset and its meta file reference
+///
+/// The asset's own GUID (written in its meta file) isn't included
+pub fn asset_references(
+    meta: &MetaFile,
+    forced_extensions: &[String],
+) -> io::Result<BTreeSet<String>> {
+    let (asset_path, meta_path) = meta.get_paths();
+    let asset_path = Path::new(&asset_path);
+
+    let mut guids = BTreeSet::<String>::new();
+
+    let record = |guid: &str| {
+        guids.insert(guid.to_string());
+        None
+    };
+
+    if asset_path.is_file() {
+        let format = AssetFormat::detect(asset_path, forced_extensions);
+
+        match format {
+            AssetFormat::Binary => {
+                let file = File::open(asset_path)?;
+                let size = file.metadata()?.len();
+
+                let (_, externals) = read_externals(&mut BufReader::new(file), size)?;
+                guids.extend(externals.into_iter().map(|external| external.guid));
+            }
+            AssetFormat::Opaque => {}
+            _ => rewrite_stream(
+                format.scanners(),
+                BufReader::new(File::open(asset_path)?),
+                io::sink(),
+                record,
+            )?,
+        }
+    }
+
+    // Importers keep references too (ex. material remaps of models)
+    let record = |guid: &str| {
+        guids.insert(guid.to_string());
+        None
+    };
+
+    rewrite_stream(
+        &[&YamlScanner],
+        BufReader::new(File::open(&meta_path)?),
+        io::sink(),
+        record,
+    )?;
+
+    guids.remove(&meta.guid);
+    Ok(guids)
+}
+
+impl DependencyGraph {
+    /// Builds the graph of everything reachable from the seed GUIDs
+    ///
+    /// Assets are scanned on several threads, references to GUIDs outside of the project
+    /// become leaf nodes (packages, built-in resources or missing assets)
+    pub fn build(
+        metas: &HashMap<String, MetaFile>,
+        roots: &[SourceRoot],
+        packages: &PackageIndex,
+        seeds: Vec<String>,
+        forced_extensions: &[String],
+        thread_count: usize,
+    ) -> Self {
+        let visited = Mutex::new(seeds.iter().cloned().collect::<HashSet<String>>());
+        let scanned = Mutex::new(Vec::<(String, BTreeSet<String>)>::new());
+
+        let queue = WorkQueue::new(thread_count, seeds);
+
+        scope(|scope| {
+            for worker in 0..queue.worker_count() {
+                let (queue, visited, scanned) = (&queue, &visited, &scanned);
+
+                scope.spawn(move || {
+                    while let Some(guid) = queue.pop(worker) {
+                        let _finishing = queue.finishing();
+
+                        if let Some(meta) = metas.get(&guid) {
+                            match asset_references(meta, forced_extensions) {
+                                Ok(references) => {
+                                    let mut visited = visited.lock().unwrap();
+
+                                    for reference in &references {
+                                        if visited.insert(reference.clone()) {
+                                            queue.push(worker, reference.clone());
+                                        }
+                                    }
+
+                                    drop(visited);
+                                    scanned.lock().unwrap().push((guid, references));
+                                }
+                                Err(error) => println!(
+                                    "[Graph]: Failed to read {:?} ({})",
+                                    meta.get_paths().0,
+                                    error
+                                ),
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        let mut graph = Self::default();
+
+        for guid in visited.into_inner().unwrap() {
+            let node = match metas.get(&guid) {
+                Some(meta) => GraphNode {
+                    guid: guid.clone(),
+                    kind: NodeKind::Asset,
+                    path: project_path_in(roots, meta),
+                    importer: meta.importer.clone(),
+                    format: Some(AssetFormat::detect(meta.get_paths().0, forced_extensions)),
+                    package: packages.find(&guid).map(|package| package.name.clone()),
+                },
+                None => GraphNode {
+                    guid: guid.clone(),
+                    kind: match packages.find(&guid) {
+                        Some(_) => NodeKind::Package,
+                        None if is_builtin_guid(&guid) => NodeKind::Builtin,
+                        None => NodeKind::Missing,
+                    },
+                    path: None,
+                    importer: String::new(),
+                    format: None,
+                    package: packages.find(&guid).map(|package| package.name.clone()),
+                },
+            };
+
+            graph.nodes.insert(guid, node);
+        }
+
+        for (from, references) in scanned.into_inner().unwrap() {
+            for to in references {
+                graph.edges.insert((from.clone(), to));
+            }
+        }
+
+        graph
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph dependencies {\n");
+
+        for node in self.nodes.values() {
+            out.push_str(&format!(
+                "  \"{}\" [label=\"{}\"",
+                node.guid,
+                dot_escape(node.path.as_deref().unwrap_or(&node.guid))
+            ));
+
+            for (key, value) in node.attributes() {
+                out.push_str(&format!(", {}=\"{}\"", key, dot_escape(&value)));
+            }
+
+            out.push_str("];\n");
+        }
+
+        for (from, to) in &self.edges {
+            out.push_str(&format!("  \"{}\" -> \"{}\";\n", from, to));
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    pub fn to_graphml(&self) -> String {
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+
+        for key in ["kind", "path", "importer", "format", "package"] {
+            out.push_str(&format!(
+                "  <key id=\"{0}\" for=\"node\" attr.name=\"{0}\" attr.type=\"string\"/>\n",
+                key
+            ));
+        }
+
+        out.push_str("  <graph id=\"dependencies\" edgedefault=\"directed\">\n");
+
+        for node in self.nodes.values() {
+            out.push_str(&format!("    <node id=\"{}\">\n", node.guid));
+
+            for (key, value) in node.attributes() {
+                out.push_str(&format!(
+                    "      <data key=\"{}\">{}</data>\n",
+                    key,
+                    xml_escape(&value)
+                ));
+            }
+
+            out.push_str("    </node>\n");
+        }
+
+        for (from, to) in &self.edges {
+            out.push_str(&format!(
+                "    <edge source=\"{}\" target=\"{}\"/>\n",
+                from, to
+            ));
+        }
+
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+
+    pub fn to_json(&self) -> String {
+        let nodes = self
+            .nodes
+            .values()
+            .map(|node| {
+                let mut entries = vec![("guid".to_string(), JsonValue::String(node.guid.clone()))];
+
+                for (key, value) in node.attributes() {
+                    entries.push((key.to_string(), JsonValue::String(value)));
+                }
+
+                JsonValue::Object(entries)
+            })
+            .collect();
+
+        let edges = self
+            .edges
+            .iter()
+            .map(|(from, to)| {
+                JsonValue::Object(vec![
+                    ("from".to_string(), JsonValue::String(from.clone())),
+                    ("to".to_string(), JsonValue::String(to.clone())),
+                ])
+            })
+            .collect();
+
+        let mut out = JsonValue::Object(vec![
+            ("nodes".to_string(), JsonValue::Array(nodes)),
+            ("edges".to_string(), JsonValue::Array(edges)),
+        ])
+        .to_pretty_string();
+
+        out.push('\n');
+        out
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, format: GraphFormat, path: P) -> io::Result<()> {
+        let contents = match format {
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::GraphMl => self.to_graphml(),
+            GraphFormat::Json => self.to_json(),
+        };
+
+        write(path, contents)
+    }
+}
+
+impl GraphNode {
+    /// Returns the known attributes of this node (everything but the GUID)
+    pub fn attributes(&self) -> Vec<(&'static str, String)> {
+        let mut attributes = vec![("kind", self.kind.name().to_string())];
+
+        if let Some(path) = &self.path {
+            attributes.push(("path", path.clone()));
+        }
+
+        if !self.importer.is_empty() {
+            attributes.push(("importer", self.importer.clone()));
+        }
+
+        if let Some(format) = &self.format {
+            attributes.push(("format", format!("{:?}", format)));
+        }
+
+        if let Some(package) = &self.package {
+            attributes.push(("package", package.clone()));
+        }
+
+        attributes
+    }
+}
+
+fn dot_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Resolves a seed given as a GUID or as a path (relative to the assets or the project)
+pub fn seed_guid(assets: &str, seed: &str) -> Option<String> {
+    if is_guid(seed.as_bytes()) {
+        return Some(seed.to_lowercase());
+    }
+
+    let path = Path::new(seed);
+
+    let candidates: Vec<PathBuf> = if path.is_absolute() || path.starts_with(assets) {
+        vec![path.to_path_buf()]
+    } else {
+        let mut candidates = vec![Path::new(assets).join(path)];
+        candidates.extend(project_root(assets).map(|project| project.join(path)));
+        candidates
+    };
+
+    candidates.into_iter().find_map(|candidate| {
+        MetaFile::read_from_path(&PathBuf::from(format!("{}.meta", candidate.display())))
+            .ok()
+            .map(|meta| meta.guid)
+    })
+}
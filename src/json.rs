@@ -0,0 +1,291 @@
+// ===================================================================================
+//  BSD 3-Clause License
+//
+//  Copyright (c) 2023-2024, Liam R. (zCubed3)
+//
+//  Redistribution and use in source and binary forms, with or without
+//  modification, are permitted provided that the following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this
+//     list of conditions and the following disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice,
+//     this list of conditions and the following disclaimer in the documentation
+//     and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its
+//     contributors may be used to endorse or promote products derived from
+//     this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+//  AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+//  IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+//  FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+//  DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+//  CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+//  OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+//  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ===================================================================================
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// A parsed JSON value, objects keep the order their keys were written in
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+
+    /// Numbers are kept as written so nothing is lost when writing them back
+    Number(String),
+
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// Parses a JSON document, returns None if it isn't valid
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut chars = text.trim_start_matches('\u{FEFF}').chars().peekable();
+
+        let value = parse_value(&mut chars)?;
+        skip_whitespace(&mut chars);
+
+        chars.peek().is_none().then_some(value)
+    }
+
+    /// Returns the value of a key if this is an object
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            Self::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the value of a key if this is an object
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut JsonValue> {
+        match self {
+            Self::Object(entries) => entries.iter_mut().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Writes the value as JSON indented by two spaces (the way Unity writes manifests)
+    pub fn to_pretty_string(&self) -> String {
+        let mut out = String::new();
+        self.write_pretty(&mut out, 0);
+        out
+    }
+
+    fn write_pretty(&self, out: &mut String, indent: usize) {
+        match self {
+            Self::Null => out.push_str("null"),
+            Self::Bool(value) => out.push_str(if *value { "true" } else { "false" }),
+            Self::Number(number) => out.push_str(number),
+            Self::String(string) => write_string(out, string),
+            Self::Array(values) if values.is_empty() => out.push_str("[]"),
+            Self::Object(entries) if entries.is_empty() => out.push_str("{}"),
+            Self::Array(values) => {
+                out.push('[');
+
+                for (index, value) in values.iter().enumerate() {
+                    out.push_str(if index == 0 { "\n" } else { ",\n" });
+                    push_indent(out, indent + 1);
+                    value.write_pretty(out, indent + 1);
+                }
+
+                out.push('\n');
+                push_indent(out, indent);
+                out.push(']');
+            }
+            Self::Object(entries) => {
+                out.push('{');
+
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    out.push_str(if index == 0 { "\n" } else { ",\n" });
+                    push_indent(out, indent + 1);
+                    write_string(out, key);
+                    out.push_str(": ");
+                    value.write_pretty(out, indent + 1);
+                }
+
+                out.push('\n');
+                push_indent(out, indent);
+                out.push('}');
+            }
+        }
+    }
+}
+
+fn push_indent(out: &mut String, indent: usize) {
+    for _ in 0..indent {
+        out.push_str("  ");
+    }
+}
+
+fn write_string(out: &mut String, string: &str) {
+    out.push('"');
+
+    for c in string.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{C}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+fn parse_value(chars: &mut Peekable<Chars>) -> Option<JsonValue> {
+    skip_whitespace(chars);
+
+    match *chars.peek()? {
+        '{' => parse_object(chars),
+        '[' => parse_array(chars),
+        '"' => parse_string(chars).map(JsonValue::String),
+        't' => parse_literal(chars, "true", JsonValue::Bool(true)),
+        'f' => parse_literal(chars, "false", JsonValue::Bool(false)),
+        'n' => parse_literal(chars, "null", JsonValue::Null),
+        _ => parse_number(chars),
+    }
+}
+
+fn parse_literal(
+    chars: &mut Peekable<Chars>,
+    literal: &str,
+    value: JsonValue,
+) -> Option<JsonValue> {
+    for expected in literal.chars() {
+        if chars.next()? != expected {
+            return None;
+        }
+    }
+
+    Some(value)
+}
+
+fn parse_number(chars: &mut Peekable<Chars>) -> Option<JsonValue> {
+    let mut number = String::new();
+
+    while let Some(c) = chars.peek() {
+        if c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E') {
+            number.push(*c);
+            chars.next();
+        } else {
+            break;
+        }
+    }
+
+    number.parse::<f64>().ok()?;
+    Some(JsonValue::Number(number))
+}
+
+fn parse_string(chars: &mut Peekable<Chars>) -> Option<String> {
+    if chars.next()? != '"' {
+        return None;
+    }
+
+    let mut string = String::new();
+
+    loop {
+        match chars.next()? {
+            '"' => return Some(string),
+            '\\' => match chars.next()? {
+                'n' => string.push('\n'),
+                't' => string.push('\t'),
+                'r' => string.push('\r'),
+                'b' => string.push('\u{8}'),
+                'f' => string.push('\u{C}'),
+                'u' => {
+                    let code: String = chars.by_ref().take(4).collect();
+                    let code = u32::from_str_radix(&code, 16).ok()?;
+
+                    // Surrogate pairs aren't worth the trouble, they become a replacement char
+                    string.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                }
+                c => string.push(c),
+            },
+            c => string.push(c),
+        }
+    }
+}
+
+fn parse_array(chars: &mut Peekable<Chars>) -> Option<JsonValue> {
+    chars.next();
+
+    let mut values = Vec::<JsonValue>::new();
+
+    skip_whitespace(chars);
+
+    if chars.peek() == Some(&']') {
+        chars.next();
+        return Some(JsonValue::Array(values));
+    }
+
+    loop {
+        values.push(parse_value(chars)?);
+        skip_whitespace(chars);
+
+        match chars.next()? {
+            ',' => continue,
+            ']' => return Some(JsonValue::Array(values)),
+            _ => return None,
+        }
+    }
+}
+
+fn parse_object(chars: &mut Peekable<Chars>) -> Option<JsonValue> {
+    chars.next();
+
+    let mut entries = Vec::<(String, JsonValue)>::new();
+
+    skip_whitespace(chars);
+
+    if chars.peek() == Some(&'}') {
+        chars.next();
+        return Some(JsonValue::Object(entries));
+    }
+
+    loop {
+        skip_whitespace(chars);
+        let key = parse_string(chars)?;
+
+        skip_whitespace(chars);
+
+        if chars.next()? != ':' {
+            return None;
+        }
+
+        entries.push((key, parse_value(chars)?));
+        skip_whitespace(chars);
+
+        match chars.next()? {
+            ',' => continue,
+            '}' => return Some(JsonValue::Object(entries)),
+            _ => return None,
+        }
+    }
+}
@@ -29,100 +29,309 @@
 //  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 // ===================================================================================
 
+mod assembly;
+mod asset_format;
+mod binary;
+mod collision;
+mod content_match;
+mod conversion;
 mod dropwatch;
+mod external;
+mod fresh_guid;
+mod fuzzy_match;
+mod graph;
+mod hash;
+mod json;
+mod manifest;
 mod meta_file;
-
-use std::collections::HashMap;
+mod name_match;
+mod options;
+mod packages;
+mod provenance;
+mod relocation;
+mod remap_table;
+mod rewrite;
+mod source_root;
+mod work_queue;
+
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs::*;
-use std::io::Read;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use std::thread::sleep;
 use std::time::Duration;
 
+use crate::assembly::*;
+use crate::collision::*;
+use crate::content_match::*;
+use crate::conversion::*;
+use crate::external::*;
+use crate::fuzzy_match::*;
+use crate::graph::*;
+use crate::manifest::*;
 use crate::meta_file::*;
-
-#[derive(Default, Debug)]
-struct AssetConversion {
-    path: String,
-    output_path: String,
-}
-
-impl PartialEq<AssetConversion> for AssetConversion {
-    fn eq(&self, other: &AssetConversion) -> bool {
-        self.path == other.path
-    }
-}
+use crate::name_match::*;
+use crate::options::*;
+use crate::packages::*;
+use crate::provenance::*;
+use crate::relocation::*;
+use crate::remap_table::*;
+use crate::source_root::*;
 
 fn print_help() {
     println!("Proper usage of prefab_converter.exe is as follows\n");
-    println!("./prefab_converter.exe [src assets path] [dst assets path] ...");
+    println!("./prefab_converter.exe [src assets path] [dst assets path] ... [options]");
     println!("\n... = Any number of valid prefab paths (in the source assets path)!");
     println!("\nExample:");
     println!("\n./prefab_converter.exe \"C:/CustomItemsSDK/Assets\" \"C:/MarrowSDK/Assets\"");
+    println!(
+        "\nThe dependency graph of a project (or of some of its assets) can be exported with:"
+    );
+    println!("\n./prefab_converter.exe graph [assets path] ... [--format dot|graphml|json]");
+    println!("\n... = Any number of asset paths or GUIDs, the whole project if there are none");
+    println!("\nWhy an asset was migrated (according to ./migration_report.txt) is explained by:");
+    println!("\n./prefab_converter.exe why [asset path or GUID]");
+
+    Options::print_help();
 }
 
-fn main() {
-    sleep(Duration::from_millis(10000u64));
+/// Reads the extensions forced to be converted from ./extensions.txt
+fn load_convert_extensions() -> Vec<String> {
+    let mut vec = Vec::<String>::new();
+
+    if let Ok(file) = read_to_string("./extensions.txt") {
+        // For each line, add it to the extension list
+        for line in file.lines() {
+            // Is this a comment?
+            if line.starts_with("#") {
+                continue;
+            }
+
+            // Empty line?
+            if line.is_empty() {
+                continue;
+            }
+
+            let str = String::from(line);
+
+            if !vec.contains(&str) {
+                vec.push(str)
+            }
+        }
+    }
+
+    vec
+}
+
+/// Exports the dependency graph of a project, or of the given seeds within it
+fn run_graph(options: &Options) {
+    let args = &options.positional[1..];
 
+    let Some(assets) = args.first() else {
+        print_help();
+        return;
+    };
+
+    let convert_extensions = load_convert_extensions();
+
+    println!("Collecting meta files...");
+    let Some(mut metas) = collect_meta_files(assets, options) else {
+        println!("Collection of the project was stopped, aborting!");
+        return;
+    };
+
+    save_meta_files(assets, &metas, options);
+
+    let packages = PackageIndex::collect(assets, options);
+    metas.extend(packages.embedded_metas.iter().cloned());
+
+    let roots = source_roots(assets, &packages);
+
+    let metas: HashMap<String, MetaFile> = metas
+        .into_iter()
+        .map(|meta| (meta.guid.clone(), meta))
+        .collect();
+
+    // Without seeds the whole project is graphed
+    let seeds: Vec<String> = if args.len() > 1 {
+        args[1..]
+            .iter()
+            .filter_map(|seed| {
+                let guid = seed_guid(assets, seed);
+
+                if guid.is_none() {
+                    println!("[Graph]: Skipping {:?}, it's not a GUID or an asset", seed);
+                }
+
+                guid
+            })
+            .collect()
+    } else {
+        metas.keys().cloned().collect()
+    };
+
+    println!("Building the dependency graph of {} seeds...", seeds.len());
+
+    let graph = DependencyGraph::build(
+        &metas,
+        &roots,
+        &packages,
+        seeds,
+        &convert_extensions,
+        options.threads,
+    );
+
+    let output = options
+        .output
+        .clone()
+        .unwrap_or_else(|| format!("./dependency_graph.{}", options.graph_format.extension()));
+
+    match graph.write(options.graph_format, &output) {
+        Ok(()) => println!(
+            "Wrote {} assets and {} references to {:?}",
+            graph.nodes.len(),
+            graph.edges.len(),
+            output
+        ),
+        Err(error) => println!("[Graph]: Failed to write {:?} ({})", output, error),
+    }
+}
+
+/// Explains why an asset was migrated using the report of the last migration
+fn run_why(options: &Options) {
+    let Some(query) = options.positional.get(1) else {
+        print_help();
+        return;
+    };
+
+    let Some(report) = ProvenanceReport::load_from_file("./migration_report.txt") else {
+        println!("[Why]: There's no ./migration_report.txt, run a migration first");
+        return;
+    };
+
+    let Some(entry) = report.find(query) else {
+        println!("[Why]: {:?} wasn't migrated by the last migration", query);
+        return;
+    };
+
+    println!("{} ({}) was migrated because of:", entry.path, entry.guid);
+
+    for (index, link) in report.chain(entry).into_iter().enumerate() {
+        if index == 0 {
+            let reason = if link.referrer.is_none() {
+                "seed"
+            } else {
+                "referrer unknown"
+            };
+            println!("\t{} ({})", link.path, reason);
+        } else {
+            println!(
+                "\t-> {} (in {})",
+                link.path,
+                link.field.as_deref().unwrap_or("an unknown field")
+            );
+        }
+    }
+}
+
+fn main() {
     // Handle arguments
-    let args: Vec<String> = env::args().collect();
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            println!("{}\n", error);
+            print_help();
+            return;
+        }
+    };
 
-    let (src_assets, dst_assets) = if args.len() > 1 {
-        // Minimum is 4
-        if args.len() < 3 {
+    if options
+        .positional
+        .first()
+        .is_some_and(|command| command == "graph")
+    {
+        run_graph(&options);
+        return;
+    }
+
+    if options
+        .positional
+        .first()
+        .is_some_and(|command| command == "why")
+    {
+        run_why(&options);
+        return;
+    }
+
+    let args = &options.positional;
+
+    let (src_assets, dst_assets) = if !args.is_empty() {
+        // Minimum is 2
+        if args.len() < 2 {
             print_help();
             return;
         }
 
-        (args[1].clone(), args[2].clone())
+        (args[0].clone(), args[1].clone())
     } else {
         print_help();
         return;
     };
 
+    // Subcommands and bad arguments don't wait, only migrations do
+    sleep(Duration::from_millis(10000u64));
+
     // Before we export, create the temp folder
     let _ = create_dir("ConversionOutput");
     let export_path = "./ConversionOutput".to_string();
 
-    let convert_extensions = {
-        let mut vec = Vec::<String>::new();
+    // Unity assets are detected by their contents, these are only extra overrides
+    let convert_extensions = load_convert_extensions();
 
-        let default_extensions = vec![".prefab", ".mat", ".asset", ".unity", ".controller"];
+    let relocator = Relocator::load_from_file("./relocations.txt");
+    let remap_table = RemapTable::load_from_file("./remaps.csv");
+    let external_assets = ExternalAssets::load_from_file("./external.txt");
 
-        for ext in default_extensions {
-            vec.push(String::from(ext));
-        }
+    println!("-- [Run Info] --");
 
-        if let Ok(file) = read_to_string("./extensions.txt") {
-            // For each line, add it to the extension list
-            for line in file.lines() {
-                // Is this a comment?
-                if line.starts_with("#") {
-                    continue;
-                }
+    println!("Target Extensions: Auto-detected Unity assets");
+    for ext in &convert_extensions {
+        println!("\t{} (forced)", ext);
+    }
 
-                // Empty line?
-                if line.is_empty() {
-                    continue;
-                }
+    if !options.src_root.is_empty() || !options.dst_root.is_empty() {
+        println!(
+            "Matching Roots: Assets/{} -> Assets/{}",
+            options.src_root, options.dst_root
+        );
+    }
 
-                let str = String::from(line);
+    // A mistyped root would silently match nothing
+    for (assets, root) in [
+        (&src_assets, &options.src_root),
+        (&dst_assets, &options.dst_root),
+    ] {
+        let root_path = Path::new(assets).join(root);
 
-                if !vec.contains(&str) {
-                    vec.push(str)
-                }
-            }
+        if !root_path.is_dir() {
+            println!("Matching root {:?} doesn't exist, aborting!", root_path);
+            return;
         }
+    }
 
-        vec
-    };
+    if !relocator.rules.is_empty() {
+        println!("Relocation Rules:");
+        for rule in &relocator.rules {
+            println!("\t{}", rule);
+        }
+    }
 
-    println!("-- [Run Info] --");
+    if !external_assets.is_empty() {
+        println!("External Assets: {} entries", external_assets.len());
+    }
 
-    println!("Target Extensions:");
-    for ext in &convert_extensions {
-        println!("\t{}", ext);
+    if !remap_table.is_empty() {
+        println!("Remap Overrides: {} rows", remap_table.rows.len());
     }
 
     println!("--============--");
@@ -133,8 +342,7 @@ fn main() {
 
     // We read two projects worth of hash files
     // Any overlap between the two is eliminated (we assume the asset already exists properly)
-    let mut missing_metas = Vec::<MetaFile>::new();
-    let mut remapped_metas = HashMap::<String, MetaFile>::new();
+    let mut mapping = MetaMapping::default();
 
     println!("-- [Collection Stage] --");
     print!("If this is the first time you've done this since rebooting");
@@ -144,176 +352,338 @@ fn main() {
 
     {
         println!("Collecting source meta files...");
-        let src_metas = collect_meta_files(&src_assets);
+        let Some(mut src_metas) = collect_meta_files(&src_assets, &options) else {
+            println!("Collection of the source project was stopped, aborting!");
+            return;
+        };
 
         println!("Collecting destination meta files...");
-        let dst_metas = collect_meta_files(&dst_assets);
+        let Some(mut dst_metas) = collect_meta_files(&dst_assets, &options) else {
+            println!("Collection of the destination project was stopped, aborting!");
+            return;
+        };
+
+        // Package assets are never copied, but references to them are worth knowing about
+        println!("Collecting package meta files...");
+        mapping.src_packages = PackageIndex::collect(&src_assets, &options);
+        mapping.dst_packages = PackageIndex::collect(&dst_assets, &options);
+
+        // Embedded packages are migrated like the assets, so they're matched the same way
+        src_metas.extend(mapping.src_packages.embedded_metas.iter().cloned());
+
+        println!(
+            "Found {} source and {} destination packages",
+            mapping.src_packages.packages.len(),
+            mapping.dst_packages.packages.len()
+        );
 
         //let drop = Dropwatch::new_begin("OVERLAPPING");
 
+        let mut collisions = Vec::<GuidCollision>::new();
+
         println!("Determining missing meta files...");
-        for src_meta in &src_metas {
-            //println!("{:?}", src_meta);
+        let dst_guids: HashMap<u64, &MetaFile> = dst_metas
+            .iter()
+            .map(|dst_meta| (dst_meta.guid_hash, dst_meta))
+            .collect();
 
-            let mut same_found = false;
+        // Paths are matched relative to these (ex. CustomItemsSDK/ and MarrowSDK/)
+        let name_matcher = NameMatcher::new(
+            Path::new(&src_assets).join(&options.src_root),
+            Path::new(&dst_assets).join(&options.dst_root),
+            &dst_metas,
+        );
 
-            for dst_meta in &dst_metas {
-                if src_meta.guid_hash == dst_meta.guid_hash {
-                    same_found = true;
-                    break;
-                }
+        // Name matches scoring below the threshold, these mustn't be matched by name again
+        let mut rejected_guids = HashSet::<String>::new();
 
-                // Is this the same asset but with a different GUID?
-                if src_meta.base_hash == dst_meta.base_hash {
-                    same_found = true;
-                    remapped_metas.insert(src_meta.guid.clone(), dst_meta.clone());
-                    break;
+        for src_meta in &src_metas {
+            if let Some(dst_meta) = dst_guids.get(&src_meta.guid_hash) {
+                // Make sure this is actually the same asset and not a copy-pasted meta
+                if let Some(collision) =
+                    check_collision(src_meta, dst_meta, options.verify_contents)
+                {
+                    if options.regenerate_collisions {
+                        mapping.regenerated_guids.insert(src_meta.guid.clone());
+                        mapping
+                            .missing_metas
+                            .insert(src_meta.guid.clone(), src_meta.clone());
+                    }
+
+                    collisions.push(collision);
                 }
+
+                continue;
             }
 
-            if !same_found {
-                missing_metas.push(src_meta.clone());
+            // Assets of installed packages already exist in the destination
+            if mapping.dst_packages.guids.contains_key(&src_meta.guid) {
+                continue;
             }
-        }
-    }
 
-    //
-    // Conversion stage
-    //
-    println!("-- [Conversion Stage] --");
-    println!("Please be patient, conversion may take a while!");
-    println!("--====================--");
+            // Is this the same asset but with a different GUID?
+            let Some(found) = name_matcher.best_match(src_meta) else {
+                mapping
+                    .missing_metas
+                    .insert(src_meta.guid.clone(), src_meta.clone());
+                continue;
+            };
+
+            let src_path = src_meta.get_paths().0;
+
+            if found.score < options.match_threshold {
+                println!(
+                    "[Collection]: Copying {:?} instead of remapping it to {:?} (score {:.2})",
+                    src_path,
+                    found.dst_meta.get_paths().0,
+                    found.score
+                );
+
+                rejected_guids.insert(src_meta.guid.clone());
+                mapping
+                    .missing_metas
+                    .insert(src_meta.guid.clone(), src_meta.clone());
+                continue;
+            }
 
-    let mut convert_queue = Vec::<AssetConversion>::new();
+            if !found.alternatives.is_empty() {
+                println!(
+                    "[Collection]: {:?} is ambiguous, remapping it to {:?} (score {:.2})",
+                    src_path,
+                    found.dst_meta.get_paths().0,
+                    found.score
+                );
+
+                for (alternative, score) in &found.alternatives {
+                    println!(
+                        "\tAlternative {:?} (score {:.2})",
+                        alternative.get_paths().0,
+                        score
+                    );
+                }
+            }
 
-    for prefab in args.iter().skip(3) {
-        let prefab_dir = PathBuf::from(prefab);
-        let mut relative_export_path = PathBuf::from(&export_path);
+            mapping
+                .remapped_metas
+                .insert(src_meta.guid.clone(), found.dst_meta);
+        }
 
-        let sanitized = {
-            if let Ok(prefab) = prefab_dir.strip_prefix(&src_assets) {
-                prefab
-            } else {
-                prefab_dir.as_path()
-            }
-        };
+        for collision in &collisions {
+            println!("[Collision]: {}", collision);
+        }
 
-        relative_export_path.push(sanitized);
-        relative_export_path.pop();
+        if !collisions.is_empty() && !options.regenerate_collisions {
+            print!("[Collision]: Found {} GUID collisions", collisions.len());
+            println!(", pass --regenerate-collisions to give the source assets new GUIDs");
+        }
+
+        // Assemblies are matched by their name, file names often differ between projects
+        for (guid, dst_meta) in match_assemblies(&src_metas, &dst_metas) {
+            println!(
+                "[Collection]: Remapping assembly {:?} to {:?}",
+                src_metas
+                    .iter()
+                    .find(|meta| meta.guid == guid)
+                    .unwrap()
+                    .base_name,
+                dst_meta.base_name
+            );
+
+            mapping.missing_metas.remove(&guid);
+            mapping.remapped_metas.insert(guid, dst_meta);
+        }
 
-        let mut import = PathBuf::from(prefab);
+        // Renamed assets can still be found by their contents
+        let missing_guids = mapping.missing_metas.keys().cloned().collect();
 
-        if !import.starts_with(&src_assets) {
-            import = PathBuf::from(&src_assets);
-            import.push(prefab);
+        for (guid, dst_meta) in match_contents(&mut src_metas, &mut dst_metas, &missing_guids) {
+            println!(
+                "[Collection]: Remapping {:?} to {:?} (identical contents)",
+                mapping.missing_metas[&guid].base_name, dst_meta.base_name
+            );
+
+            mapping.missing_metas.remove(&guid);
+            mapping.regenerated_guids.remove(&guid);
+            mapping.remapped_metas.insert(guid, dst_meta);
         }
 
-        convert_queue.push(AssetConversion {
-            path: import.display().to_string(),
-            output_path: relative_export_path.display().to_string(),
-        });
-    }
+        // Similar names are a last resort, only used when asked for since they may be wrong
+        if options.fuzzy {
+            let fuzzy_matcher = FuzzyMatcher::new(
+                ExtensionClasses::load_from_file("./extension_classes.txt"),
+                options.fuzzy_distance,
+                &dst_metas,
+            );
+
+            for src_meta in &src_metas {
+                if !mapping.missing_metas.contains_key(&src_meta.guid)
+                    || mapping.regenerated_guids.contains(&src_meta.guid)
+                    || rejected_guids.contains(&src_meta.guid)
+                {
+                    continue;
+                }
+
+                if let Some(found) = fuzzy_matcher.find(src_meta) {
+                    println!(
+                        "[Collection]: Fuzzy remapping {:?} to {:?} (edit distance {})",
+                        src_meta.get_paths().0,
+                        found.dst_meta.get_paths().0,
+                        found.distance
+                    );
+
+                    mapping.missing_metas.remove(&src_meta.guid);
+                    mapping
+                        .remapped_metas
+                        .insert(src_meta.guid.clone(), found.dst_meta);
+                }
+            }
+        }
 
-    while let Some(convert) = convert_queue.pop() {
-        let prefab_path = Path::new(&convert.path);
+        // Externally provided assets are left alone no matter what matched them
+        mapping.external_guids = external_assets.guids.clone();
 
-        if prefab_path.is_dir() {
-            continue;
+        for src_meta in &src_metas {
+            if external_assets.contains(&src_assets, src_meta) {
+                mapping.external_guids.insert(src_meta.guid.clone());
+            }
         }
 
-        let mut prefab_file = File::open(prefab_path).unwrap();
+        for guid in &mapping.external_guids {
+            mapping.missing_metas.remove(guid);
+            mapping.remapped_metas.remove(guid);
+            mapping.regenerated_guids.remove(guid);
+        }
 
-        // Copy over the meta file first (if it doesn't exist)
-        let mut meta_path = prefab_path.display().to_string();
-        meta_path.push_str(".meta");
+        // Package assets are addressed by their project path (ex. Packages/com.company.package/)
+        let src_roots = source_roots(&src_assets, &mapping.src_packages);
+        let dst_roots = [SourceRoot::assets(&dst_assets)];
 
-        let mut contents = String::new();
-        let _size = prefab_file.read_to_string(&mut contents);
+        mapping.overrides = remap_table.resolve(&src_roots, &src_metas, &dst_roots, &dst_metas);
 
-        let mut converted_contents = contents.clone();
+        save_meta_files(&src_assets, &src_metas, &options);
+        save_meta_files(&dst_assets, &dst_metas, &options);
 
-        // Find all occurrences of "guid"
-        for indice in contents.match_indices("guid: ") {
-            let guid: String = contents.chars().skip(indice.0 + 6).take(32).collect();
+        for dst_meta in dst_metas {
+            mapping.dst_metas.insert(dst_meta.guid.clone(), dst_meta);
+        }
+    }
 
-            // Check if this has been remapped
-            if let Some(meta_file) = remapped_metas.get(&guid) {
-                converted_contents.replace_range(indice.0 + 6..indice.0 + 6 + 32, &meta_file.guid);
-                continue;
-            }
+    //
+    // Conversion stage
+    //
+    println!("-- [Conversion Stage] --");
+    println!("Please be patient, conversion may take a while!");
+    println!("--====================--");
 
-            // Check if this is in our list of missing ones
-            // If so copy it
-            let mut need_delete = false;
-            let mut delete = 0usize;
+    let mut converter = Converter::new(
+        src_assets,
+        dst_assets,
+        export_path,
+        convert_extensions,
+        relocator,
+        mapping,
+        options.guid_mode.clone(),
+    );
 
-            for missing_meta in &missing_metas {
-                if missing_meta.guid == guid {
-                    // Copy the asset (with and without the meta over)
-                    // If the file doesn't exist already, copy it
-                    let prefab_dir = PathBuf::from(&missing_meta.directory);
-                    let mut relative_export_path = PathBuf::from(&export_path);
-                    relative_export_path.push(prefab_dir.strip_prefix(&src_assets).unwrap());
+    let mut convert_queue = Vec::<AssetConversion>::new();
 
-                    let export_path = relative_export_path.display().to_string();
-                    let _ = create_dir_all(&export_path);
+    for prefab in args.iter().skip(2) {
+        match converter.seed(prefab) {
+            Some(seed) => convert_queue.push(seed),
+            None => println!(
+                "[Conversion]: Skipping {:?}, it's outside of every source root",
+                prefab
+            ),
+        }
+    }
 
-                    let (asset_src_path, meta_src_path) = missing_meta.get_paths();
-                    let (asset_dst_path, meta_dst_path) = missing_meta.get_paths_stem(&export_path);
+    converter.run(convert_queue, options.threads);
 
-                    if Path::new(&asset_src_path).exists() && !Path::new(&asset_dst_path).exists() {
-                        copy(&asset_src_path, &asset_dst_path).unwrap();
-                    }
+    let provenance = converter.provenance();
 
-                    if Path::new(&meta_src_path).exists() && !Path::new(&meta_dst_path).exists() {
-                        copy(&meta_src_path, &meta_dst_path).unwrap();
-                    }
+    match provenance.save("./migration_report.txt") {
+        Ok(()) => println!(
+            "[Report]: Wrote the provenance of {} assets to ./migration_report.txt",
+            provenance.entries.len()
+        ),
+        Err(error) => println!(
+            "[Report]: Failed to write ./migration_report.txt ({})",
+            error
+        ),
+    }
 
-                    // If this is a prefab, push it to the list of queued conversions
-                    // If it hasn't been pushed already!
-                    for ext in &convert_extensions {
-                        if missing_meta.base_name.ends_with(ext.as_str())
-                            && !convert_queue.iter().any(|e| e.path == asset_src_path)
-                        {
-                            println!(
-                                "[Conversion]: Enqueuing referenced asset {:?}",
-                                asset_src_path
-                            );
-
-                            convert_queue.push(AssetConversion {
-                                path: asset_src_path.clone(),
-                                output_path: relative_export_path.display().to_string(),
-                            });
-
-                            break;
-                        }
-                    }
+    if converter.fresh_guid_count() > 0 {
+        println!(
+            "[Conversion]: Gave {} copied assets new GUIDs",
+            converter.fresh_guid_count()
+        );
+    }
 
-                    // After being successfully copied, this is removed from the missing list
-                    // This prevents prefab duplication / overwriting
-                    need_delete = true;
-                    break;
-                }
+    let applied_overrides = converter.applied_overrides();
 
-                delete += 1;
-            }
+    if !applied_overrides.is_empty() {
+        println!(
+            "[Overrides]: Applied {} of {} remap overrides",
+            applied_overrides.len(),
+            converter.mapping.overrides.len()
+        );
 
-            if need_delete {
-                missing_metas.remove(delete);
-            }
+        for remap in applied_overrides {
+            println!("\t{}", remap);
         }
+    }
+
+    let required_packages = converter.required_packages();
+
+    if !required_packages.is_empty() {
+        println!(
+            "[Packages]: Migrated assets need {} packages",
+            required_packages.len()
+        );
+
+        let dst_manifest = PackageManifest::load(&converter.dst_assets);
+        let mut dependencies = Vec::<(String, String)>::new();
+
+        for package in required_packages {
+            let installed = converter
+                .mapping
+                .dst_packages
+                .find_by_name(&package.name)
+                .is_some()
+                || dst_manifest
+                    .as_ref()
+                    .is_some_and(|manifest| manifest.dependency(&package.name).is_some());
+
+            let status = if installed {
+                "installed".to_string()
+            } else if let Some(version) = source_dependency(&converter.src_assets, package) {
+                dependencies.push((package.name.clone(), version));
+                "missing from the destination".to_string()
+            } else {
+                format!(
+                    "missing from the destination, copy {:?} over by hand",
+                    package.path
+                )
+            };
 
-        let _ = create_dir_all(&convert.output_path);
+            println!("\t{}@{} ({})", package.name, package.version, status);
+        }
 
-        let mut file_path = PathBuf::from(convert.output_path);
-        file_path.push(prefab_path.file_name().unwrap());
+        if !dependencies.is_empty() {
+            add_dependencies(&converter.dst_assets, &dependencies, options.add_packages);
+        }
+    }
 
-        write(&file_path, converted_contents).unwrap();
+    let unresolved_guids = converter.unresolved_guids();
 
-        let mut extension = file_path.extension().unwrap().to_str().unwrap().to_string();
-        extension.push_str(".meta");
+    if !unresolved_guids.is_empty() {
+        println!(
+            "[Conversion]: {} referenced GUIDs weren't found in either project",
+            unresolved_guids.len()
+        );
 
-        file_path.set_extension(extension);
-        let _ = copy(meta_path, &file_path);
+        for guid in unresolved_guids {
+            println!("\t{}", guid);
+        }
     }
 }
@@ -0,0 +1,160 @@
+// ===================================================================================
+//  BSD 3-Clause License
+//
+//  Copyright (c) 2023-2024, Liam R. (zCubed3)
+//
+//  Redistribution and use in source and binary forms, with or without
+//  modification, are permitted provided that the following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this
+//     list of conditions and the following disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice,
+//     this list of conditions and the following disclaimer in the documentation
+//     and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its
+//     contributors may be used to endorse or promote products derived from
+//     this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+//  AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+//  IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+//  FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+//  DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+//  CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+//  OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+//  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ===================================================================================
+
+use std::fs::{read_to_string, write};
+use std::io;
+use std::path::PathBuf;
+
+use crate::json::JsonValue;
+use crate::packages::{project_root, Package};
+
+/// A project's Packages/manifest.json
+#[derive(Debug)]
+pub struct PackageManifest {
+    pub path: PathBuf,
+    pub json: JsonValue,
+}
+
+impl PackageManifest {
+    /// Loads the manifest of the project owning an assets folder
+    pub fn load(assets: &str) -> Option<Self> {
+        let path = project_root(assets)?.join("Packages").join("manifest.json");
+        let json = JsonValue::parse(&read_to_string(&path).ok()?)?;
+
+        Some(Self { path, json })
+    }
+
+    /// Returns the version (or URL / path) a package is listed with
+    pub fn dependency(&self, name: &str) -> Option<&str> {
+        self.json.get("dependencies")?.get(name)?.as_str()
+    }
+
+    /// Adds a dependency, keeping the dependencies sorted like Unity does
+    pub fn add_dependency(&mut self, name: &str, version: &str) -> bool {
+        let JsonValue::Object(root) = &mut self.json else {
+            return false;
+        };
+
+        if !root.iter().any(|(key, _)| key == "dependencies") {
+            root.insert(0, ("dependencies".into(), JsonValue::Object(Vec::new())));
+        }
+
+        let Some(JsonValue::Object(dependencies)) = self.json.get_mut("dependencies") else {
+            return false;
+        };
+
+        let index = dependencies
+            .iter()
+            .position(|(key, _)| key.as_str() > name)
+            .unwrap_or(dependencies.len());
+
+        dependencies.insert(index, (name.into(), JsonValue::String(version.into())));
+        true
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let mut contents = self.json.to_pretty_string();
+        contents.push('\n');
+
+        write(&self.path, contents)
+    }
+}
+
+/// Returns what a package was installed as in the source project
+///
+/// The manifest is preferred since it keeps git URLs and local paths intact, packages only
+/// pulled in as dependencies are found in packages-lock.json or the package cache instead
+pub fn source_dependency(src_assets: &str, package: &Package) -> Option<String> {
+    if let Some(version) = PackageManifest::load(src_assets)
+        .as_ref()
+        .and_then(|manifest| manifest.dependency(&package.name))
+    {
+        return Some(version.to_string());
+    }
+
+    if let Some(version) = locked_version(src_assets, &package.name) {
+        return Some(version);
+    }
+
+    // Embedded packages can only be copied over, there is no version to depend on
+    (!package.embedded && !package.version.is_empty()).then(|| package.version.clone())
+}
+
+/// Returns the version of a package in the packages-lock.json of a project
+fn locked_version(assets: &str, name: &str) -> Option<String> {
+    let path = project_root(assets)?
+        .join("Packages")
+        .join("packages-lock.json");
+
+    let json = JsonValue::parse(&read_to_string(path).ok()?)?;
+    let entry = json.get("dependencies")?.get(name)?;
+
+    // Embedded and local packages are locked by path, those aren't usable elsewhere
+    match entry.get("source").and_then(JsonValue::as_str) {
+        Some("embedded") | Some("local") => None,
+        _ => Some(entry.get("version")?.as_str()?.to_string()),
+    }
+}
+
+/// Adds dependencies to a project's manifest, or prints them if the manifest isn't written
+pub fn add_dependencies(assets: &str, dependencies: &[(String, String)], write_manifest: bool) {
+    let manifest = PackageManifest::load(assets);
+
+    if let (true, Some(mut manifest)) = (write_manifest, manifest) {
+        for (name, version) in dependencies {
+            manifest.add_dependency(name, version);
+        }
+
+        match manifest.save() {
+            Ok(()) => println!(
+                "[Packages]: Added {} dependencies to {:?}",
+                dependencies.len(),
+                manifest.path
+            ),
+            Err(error) => println!(
+                "[Packages]: Failed to write {:?} ({})",
+                manifest.path, error
+            ),
+        }
+
+        return;
+    }
+
+    println!("[Packages]: Add these dependencies to the destination Packages/manifest.json");
+
+    for (name, version) in dependencies {
+        println!("\t+ \"{}\": \"{}\",", name, version);
+    }
+
+    if !write_manifest {
+        println!("[Packages]: Pass --add-packages to add them automatically");
+    }
+}
@@ -29,65 +29,62 @@
 //  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 // ===================================================================================
 
+pub mod cache;
 pub mod collector;
+#[allow(clippy::module_inception)]
 pub mod meta_file;
 
+pub use cache::*;
 pub use collector::*;
 pub use meta_file::*;
 
-use std::fs::read_dir;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
+use std::sync::Arc;
 
-pub fn collect_recurse<P: AsRef<Path>>(path: P, dirs: &mut Vec<PathBuf>) {
-    for entry_result in read_dir(path).expect("Failed to read given path!") {
-        // If we can't read a meta file we probably shouldn't be in here
-        let entry = entry_result.expect("Failed to read file in given path!");
+use crate::options::Options;
 
-        if let Ok(file_type) = entry.file_type() {
-            if file_type.is_dir() {
-                dirs.push(entry.path());
-                collect_recurse(entry.path(), dirs);
-            }
-        }
-    }
-}
+/// Collects every meta file within a project, returns None if collection was cancelled
+pub fn collect_meta_files(path: &String, options: &Options) -> Option<Vec<MetaFile>> {
+    let cache = if options.use_cache {
+        let cache = MetaCache::load(path);
 
-pub fn collect_meta_files(path: &String) -> Vec<MetaFile> {
-    // First fetch all the directories within a project
-    let mut dirs = Vec::<PathBuf>::new();
-    collect_recurse(path, &mut dirs);
+        if !cache.is_empty() {
+            println!("Loaded {} cached meta files for {:?}", cache.len(), path);
+        }
 
-    // Then collect them
-    //println!("Collecting meta files...");
-    let collect_multi = true;
+        cache
+    } else {
+        MetaCache::default()
+    };
 
-    return if collect_multi {
-        //let drop = dropwatch::Dropwatch::new_begin("META_COLLECT");
+    // Directories are walked while collecting, so we only need the root here
+    //let drop = dropwatch::Dropwatch::new_begin("META_COLLECT");
 
-        let collector = MetaFileCollector::new(dirs);
-        collector.wait();
+    let collector = MetaFileCollector::new(
+        PathBuf::from(path),
+        Arc::new(cache),
+        options.threads,
+        options.strict,
+    );
 
-        collector.consume()
-    } else {
-        //let drop = dropwatch::Dropwatch::new_begin("META_COLLECT");
+    let result = collector.finish();
 
-        let mut metas = Vec::<MetaFile>::new();
-        for path in dirs {
-            for entry_result in read_dir(path).expect("Failed to read given path!") {
-                // If we can't read a meta file we probably shouldn't be in here
-                let entry = entry_result.expect("Failed to read file in given path!");
+    for error in &result.errors {
+        println!("[Collection]: Failed to read {}", error);
+    }
 
-                if let Some(extension) = entry.path().extension() {
-                    if extension == "meta" {
-                        let meta = MetaFile::read_from_path(&entry.path()).unwrap();
+    if result.cancelled {
+        return None;
+    }
 
-                        //println!("{:?}", meta);
-                        metas.push(meta);
-                    }
-                }
-            }
-        }
+    Some(result.meta_files)
+}
 
-        metas
-    };
+/// Writes collected meta files to the cache (if enabled)
+///
+/// This happens after matching so content hashes computed along the way are kept too
+pub fn save_meta_files(path: &String, metas: &[MetaFile], options: &Options) {
+    if options.use_cache {
+        MetaCache::save(path, metas);
+    }
 }
@@ -0,0 +1,186 @@
+// ===================================================================================
+//  BSD 3-Clause License
+//
+//  Copyright (c) 2023-2024, Liam R. (zCubed3)
+//
+//  Redistribution and use in source and binary forms, with or without
+//  modification, are permitted provided that the following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this
+//     list of conditions and the following disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice,
+//     this list of conditions and the following disclaimer in the documentation
+//     and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its
+//     contributors may be used to endorse or promote products derived from
+//     this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+//  AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+//  IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+//  FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+//  DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+//  CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+//  OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+//  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ===================================================================================
+
+use std::collections::HashMap;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::meta_file::{file_stamp, ContentHash, MetaFile};
+use crate::hash::stable_hash;
+
+const CACHE_HEADER: &str = "# asset_migrator meta cache v2";
+const CACHE_FOLDER: &str = "./MigratorCache";
+
+/// Previously collected meta files of a project, keyed by their meta path
+///
+/// Meta files are only re-read when their modification time or size changes
+#[derive(Debug, Default)]
+pub struct MetaCache {
+    entries: HashMap<String, MetaFile>,
+}
+
+impl MetaCache {
+    /// Returns where the cache for a given project lives
+    pub fn cache_path<P: AsRef<Path>>(project: P) -> PathBuf {
+        let project = project.as_ref();
+        let canonical = project
+            .canonicalize()
+            .unwrap_or_else(|_| project.to_path_buf());
+
+        let mut path = PathBuf::from(CACHE_FOLDER);
+        path.push(format!(
+            "{:016x}.cache",
+            stable_hash(canonical.display().to_string())
+        ));
+
+        path
+    }
+
+    /// Loads the cache of a project, returns an empty cache if there isn't a valid one
+    pub fn load<P: AsRef<Path>>(project: P) -> Self {
+        let mut cache = Self::default();
+
+        let Ok(file) = read_to_string(Self::cache_path(project)) else {
+            return cache;
+        };
+
+        let mut lines = file.lines();
+
+        if lines.next() != Some(CACHE_HEADER) {
+            return cache;
+        }
+
+        for line in lines {
+            if let Some(meta) = Self::parse_entry(line) {
+                cache.entries.insert(meta.get_meta_path(), meta);
+            }
+        }
+
+        cache
+    }
+
+    fn parse_entry(line: &str) -> Option<MetaFile> {
+        let mut fields = line.split('\t');
+
+        let meta_path = PathBuf::from(fields.next()?);
+        let guid = fields.next()?.to_string();
+        let guid_hash = fields.next()?.parse().ok()?;
+        let base_hash = fields.next()?.parse().ok()?;
+        let importer = fields.next()?.to_string();
+        let modified = fields.next()?.parse().ok()?;
+        let size = fields.next()?.parse().ok()?;
+
+        // Content hashes are optional, most assets never need one
+        let content = (|| {
+            Some(ContentHash {
+                modified: fields.next()?.parse().ok()?,
+                size: fields.next()?.parse().ok()?,
+                hash: fields.next()?.parse().ok()?,
+            })
+        })();
+
+        Some(MetaFile {
+            directory: meta_path.parent()?.display().to_string(),
+            base_name: meta_path.file_stem()?.to_str()?.to_string(),
+            guid,
+            guid_hash,
+            base_hash,
+            importer,
+            modified,
+            size,
+            content,
+        })
+    }
+
+    /// Writes the given meta files as the new cache of a project
+    pub fn save<P: AsRef<Path>>(project: P, metas: &[MetaFile]) {
+        let mut contents = String::from(CACHE_HEADER);
+        contents.push('\n');
+
+        for meta in metas {
+            contents.push_str(&format!(
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                meta.get_meta_path(),
+                meta.guid,
+                meta.guid_hash,
+                meta.base_hash,
+                meta.importer,
+                meta.modified,
+                meta.size
+            ));
+
+            if let Some(content) = &meta.content {
+                contents.push_str(&format!(
+                    "\t{}\t{}\t{}",
+                    content.modified, content.size, content.hash
+                ));
+            }
+
+            contents.push('\n');
+        }
+
+        let _ = create_dir_all(CACHE_FOLDER);
+
+        if let Err(error) = write(Self::cache_path(project), contents) {
+            println!("[Cache]: Failed to write meta cache ({})", error);
+        }
+    }
+
+    /// Returns the cached meta file if it is still up to date
+    pub fn lookup(&self, meta_path: &str, modified: u64, size: u64) -> Option<MetaFile> {
+        self.entries
+            .get(meta_path)
+            .filter(|meta| meta.modified == modified && meta.size == size)
+            .cloned()
+    }
+
+    /// Reads a meta file unless an up to date copy is already cached
+    pub fn read_or_lookup(&self, meta_path: &PathBuf) -> io::Result<MetaFile> {
+        if let Ok(metadata) = meta_path.metadata() {
+            let (modified, size) = file_stamp(&metadata);
+
+            if let Some(meta) = self.lookup(&meta_path.display().to_string(), modified, size) {
+                return Ok(meta);
+            }
+        }
+
+        MetaFile::read_from_path(meta_path)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
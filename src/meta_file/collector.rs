@@ -29,132 +29,193 @@
 //  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 // ===================================================================================
 
+use std::fmt;
 use std::fs::read_dir;
-use std::path::PathBuf;
-use std::sync::{Arc, Condvar, Mutex};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
 use std::thread::{spawn, JoinHandle};
 
+use super::cache::MetaCache;
 use super::meta_file::MetaFile;
+use crate::work_queue::WorkQueue;
 
-/// Spawns threads and collects meta files from an internal worklist
+/// A directory or meta file that couldn't be read during collection
+#[derive(Debug)]
+pub struct CollectError {
+    pub path: PathBuf,
+    pub error: io::Error,
+}
+
+impl fmt::Display for CollectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {}", self.path, self.error)
+    }
+}
+
+/// Everything a collector found, sorted by path so runs are deterministic
+#[derive(Debug, Default)]
+pub struct CollectResult {
+    pub meta_files: Vec<MetaFile>,
+    pub errors: Vec<CollectError>,
+
+    /// True if collection was stopped before the whole project was walked
+    pub cancelled: bool,
+}
+
+enum CollectMessage {
+    Metas(Vec<MetaFile>),
+    Error(CollectError),
+}
+
+/// Spawns threads that walk a project and collect its meta files
+///
+/// Directories found while walking are queued as more work, so parsing meta files
+/// and discovering directories happens at the same time
 pub struct MetaFileCollector {
     threads: Vec<JoinHandle<()>>,
-    meta_files: Arc<Mutex<Vec<MetaFile>>>,
-    condvar: Arc<(Mutex<bool>, Condvar)>,
+    receiver: Receiver<CollectMessage>,
+    cancelled: Arc<AtomicBool>,
+    stop_on_error: bool,
 }
 
 impl MetaFileCollector {
-    pub fn new(paths: Vec<PathBuf>) -> Self {
+    pub fn new(
+        root: PathBuf,
+        cache: Arc<MetaCache>,
+        thread_count: usize,
+        stop_on_error: bool,
+    ) -> Self {
         let mut threads = Vec::<JoinHandle<()>>::new();
-        let work_paths = Arc::new(Mutex::new(paths));
-        let meta_files = Arc::new(Mutex::new(Vec::<MetaFile>::new()));
-        let condvar = Arc::new((Mutex::new(false), Condvar::new()));
-
-        // TODO: Get hardware concurrency?
-        let thread_count = std::thread::available_parallelism().unwrap().get();
+        let work_paths = Arc::new(WorkQueue::new(thread_count, vec![root]));
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = channel::<CollectMessage>();
 
         #[cfg(debug_assertions)]
         {
-            println!("DEBUG: COLLECTING WITH {} THREADS", thread_count);
+            println!(
+                "DEBUG: COLLECTING WITH {} THREADS",
+                work_paths.worker_count()
+            );
         }
 
-        for _ in 0usize..thread_count {
+        for worker in 0usize..work_paths.worker_count() {
             let work_paths = Arc::clone(&work_paths);
-            let meta_files = Arc::clone(&meta_files);
-            let condvar = Arc::clone(&condvar);
+            let cancelled = Arc::clone(&cancelled);
+            let cache = Arc::clone(&cache);
+            let sender = sender.clone();
 
             threads.push(spawn(move || {
-                MetaFileCollector::collector_loop(condvar, work_paths, meta_files)
+                MetaFileCollector::collector_loop(worker, work_paths, sender, cancelled, cache)
             }));
         }
 
         Self {
             threads,
-            meta_files,
-            condvar,
+            receiver,
+            cancelled,
+            stop_on_error,
         }
     }
 
-    pub fn wait(&self) {
-        {
-            let (lock, cvar) = &*self.condvar;
-
-            let mut notified = lock.lock().unwrap();
-            notified = cvar.wait(notified).unwrap();
-        }
+    /// Stops collection, directories that are still queued are skipped
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
     }
 
-    pub fn consume(self) -> Vec<MetaFile> {
-        // Ensure all the threads have exited first
-        loop {
-            let mut all_finished = true;
-            for thread in &self.threads {
-                if !thread.is_finished() {
-                    all_finished = false;
-                    break;
+    /// Waits for every thread to exit and returns what was collected
+    pub fn finish(self) -> CollectResult {
+        let mut result = CollectResult::default();
+
+        // The channel closes once every thread has exited and dropped its sender
+        for message in &self.receiver {
+            match message {
+                CollectMessage::Metas(mut metas) => result.meta_files.append(&mut metas),
+                CollectMessage::Error(error) => {
+                    if self.stop_on_error {
+                        self.cancel();
+                    }
+
+                    result.errors.push(error);
                 }
             }
+        }
 
-            if all_finished {
-                break;
-            }
+        for thread in self.threads {
+            thread.join().expect("Meta file collector thread panicked!");
         }
 
-        return Arc::try_unwrap(self.meta_files)
-            .unwrap()
-            .into_inner()
-            .unwrap();
+        result.cancelled = self.cancelled.load(Ordering::SeqCst);
+
+        result
+            .meta_files
+            .sort_by(|a, b| (&a.directory, &a.base_name).cmp(&(&b.directory, &b.base_name)));
+        result.errors.sort_by(|a, b| a.path.cmp(&b.path));
+
+        result
     }
 
     fn collector_loop(
-        condvar: Arc<(Mutex<bool>, Condvar)>,
-        work_paths: Arc<Mutex<Vec<PathBuf>>>,
-        meta_files: Arc<Mutex<Vec<MetaFile>>>,
+        worker: usize,
+        work_paths: Arc<WorkQueue<PathBuf>>,
+        sender: Sender<CollectMessage>,
+        cancelled: Arc<AtomicBool>,
+        cache: Arc<MetaCache>,
     ) {
-        loop {
-            let path: Option<PathBuf>;
-            let notify: bool;
-
-            {
-                let mut lock = work_paths.lock().unwrap();
-                path = lock.pop();
-                notify = lock.is_empty();
-            }
-
-            if let Some(path) = path {
-                // Read the files first
-                let mut metas = Vec::<MetaFile>::new();
+        while let Some(path) = work_paths.pop(worker) {
+            let _finishing = work_paths.finishing();
 
-                for entry_result in read_dir(path).expect("Failed to read given path!") {
-                    // If we can't read a meta file we probably shouldn't be in here
-                    let entry = entry_result.expect("Failed to read file in given path!");
-
-                    if let Some(extension) = entry.path().extension() {
-                        if extension == "meta" {
-                            let meta = MetaFile::read_from_path(&entry.path()).unwrap();
+            // Cancelled work is still popped and finished so the queue drains
+            if !cancelled.load(Ordering::SeqCst) {
+                Self::collect_directory(worker, &path, &work_paths, &sender, &cache);
+            }
+        }
+    }
 
-                            //println!("{:?}", meta);
-                            metas.push(meta);
-                        }
-                    }
+    fn collect_directory(
+        worker: usize,
+        path: &Path,
+        work_paths: &WorkQueue<PathBuf>,
+        sender: &Sender<CollectMessage>,
+        cache: &MetaCache,
+    ) {
+        let error = |path: PathBuf, error: io::Error| {
+            let _ = sender.send(CollectMessage::Error(CollectError { path, error }));
+        };
+
+        let entries = match read_dir(path) {
+            Ok(entries) => entries,
+            Err(err) => return error(path.to_path_buf(), err),
+        };
+
+        let mut metas = Vec::<MetaFile>::new();
+
+        for entry_result in entries {
+            let entry = match entry_result {
+                Ok(entry) => entry,
+                Err(err) => {
+                    error(path.to_path_buf(), err);
+                    continue;
                 }
+            };
 
-                {
-                    let mut lock = meta_files.lock().unwrap();
-                    lock.append(&mut metas);
+            if let Ok(file_type) = entry.file_type() {
+                if file_type.is_dir() {
+                    work_paths.push(worker, entry.path());
+                    continue;
                 }
+            }
 
-                if notify {
-                    let (lock, cvar) = &*condvar;
-
-                    let mut notified = lock.lock().unwrap();
-                    *notified = true;
-
-                    cvar.notify_one();
+            if entry.path().extension().is_some_and(|ext| ext == "meta") {
+                match cache.read_or_lookup(&entry.path()) {
+                    Ok(meta) => metas.push(meta),
+                    Err(err) => error(entry.path(), err),
                 }
-            } else {
-                break;
             }
         }
+
+        let _ = sender.send(CollectMessage::Metas(metas));
     }
 }
@@ -29,11 +29,12 @@
 //  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 // ===================================================================================
 
-use std::collections::hash_map::DefaultHasher;
-use std::fs::File;
-use std::hash::{Hash, Hasher};
-use std::io::{BufRead, BufReader};
+use std::fs::{File, Metadata};
+use std::io::{self, BufRead, BufReader};
 use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::hash::{stable_hash, stable_hash_file};
 
 /// Unity meta file (GUID only)
 #[derive(Debug, Default, Clone)]
@@ -52,51 +53,133 @@ pub struct MetaFile {
 
     /// Hash of the base name
     pub base_hash: u64,
+
+    /// The importer Unity uses for this asset (ex. TextureImporter)
+    pub importer: String,
+
+    /// Modification time of the meta file in nanoseconds (used by the cache)
+    pub modified: u64,
+
+    /// Size of the meta file in bytes (used by the cache)
+    pub size: u64,
+
+    /// Hash of the asset's contents, only computed when needed
+    pub content: Option<ContentHash>,
+}
+
+/// Hash of an asset's contents along with the stamp of the asset when it was hashed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContentHash {
+    pub modified: u64,
+    pub size: u64,
+    pub hash: u64,
+}
+
+/// Returns the modification time (in nanoseconds) and size of a file
+pub fn file_stamp(metadata: &Metadata) -> (u64, u64) {
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or_default();
+
+    (modified, metadata.len())
 }
 
 impl MetaFile {
     /// Reads a meta file, grabs the GUID and returns it
-    pub fn read_from_path(path: &PathBuf) -> Option<Self> {
-        if let Ok(file) = File::open(path) {
-            let reader = BufReader::new(file);
-
-            let mut meta_file = Self::default();
-
-            meta_file.base_name = path
-                .file_stem()
-                .unwrap()
-                .to_os_string()
-                .into_string()
-                .unwrap();
-            meta_file.directory = path.parent().unwrap().display().to_string();
-
-            {
-                let mut hasher = DefaultHasher::new();
-                meta_file.base_name.hash(&mut hasher);
-                meta_file.base_hash = hasher.finish();
+    ///
+    /// Fails if the file can't be opened, its name isn't valid UTF-8 or it has no GUID
+    pub fn read_from_path(path: &PathBuf) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message);
+
+        let base_name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| invalid("meta file name isn't valid UTF-8"))?
+            .to_string();
+
+        let directory = path
+            .parent()
+            .map(|parent| parent.display().to_string())
+            .unwrap_or_default();
+
+        let file = File::open(path)?;
+        let (modified, size) = file.metadata().map(|m| file_stamp(&m)).unwrap_or_default();
+        let reader = BufReader::new(file);
+
+        let mut meta_file = Self {
+            base_name,
+            directory,
+            modified,
+            size,
+            ..Default::default()
+        };
+
+        meta_file.base_hash = stable_hash(&meta_file.base_name);
+
+        for contents in reader.lines().map_while(Result::ok) {
+            if meta_file.guid.is_empty() && contents.contains("guid: ") {
+                meta_file.guid = contents.replace("guid:", "").trim().to_string();
+
+                // Hashing the GUID makes overlap comparison BLAZING FAST :P
+                meta_file.guid_hash = stable_hash(&meta_file.guid);
             }
 
-            for line in reader.lines() {
-                if let Ok(contents) = line {
-                    if contents.contains("guid: ") {
-                        meta_file.guid = contents.replace("guid:", "").trim().to_string();
-
-                        // Hashing the GUID makes overlap comparison BLAZING FAST :P
-                        let mut hasher = DefaultHasher::new();
-                        meta_file.guid.hash(&mut hasher);
-                        meta_file.guid_hash = hasher.finish();
-
-                        break;
-                    }
-                }
+            // Importers are always top level keys (ex. "TextureImporter:")
+            if contents.ends_with("Importer:") && !contents.starts_with(' ') {
+                meta_file.importer = contents.trim_end_matches(':').to_string();
             }
 
-            if !meta_file.guid.is_empty() {
-                return Some(meta_file);
+            if !meta_file.guid.is_empty() && !meta_file.importer.is_empty() {
+                break;
             }
         }
 
-        return None;
+        if meta_file.guid.is_empty() {
+            return Err(invalid("meta file has no GUID"));
+        }
+
+        Ok(meta_file)
+    }
+
+    /// Returns the modification time and size of the asset, None for folders
+    pub fn asset_stamp(&self) -> Option<(u64, u64)> {
+        let metadata = Path::new(&self.get_paths().0).metadata().ok()?;
+
+        if !metadata.is_file() {
+            return None;
+        }
+
+        Some(file_stamp(&metadata))
+    }
+
+    /// Returns the hash of the asset's contents, the asset is only read if it changed
+    pub fn content_hash(&mut self) -> Option<u64> {
+        let (modified, size) = self.asset_stamp()?;
+
+        let cached = self
+            .content
+            .filter(|content| content.modified == modified && content.size == size);
+
+        if let Some(content) = cached {
+            return Some(content.hash);
+        }
+
+        let hash = stable_hash_file(self.get_paths().0).ok()?;
+        self.content = Some(ContentHash {
+            modified,
+            size,
+            hash,
+        });
+
+        Some(hash)
+    }
+
+    /// Returns the path of the meta file itself
+    pub fn get_meta_path(&self) -> String {
+        self.get_paths().1
     }
 
     /// Returns the asset and meta file paths with a new stem
@@ -110,11 +193,11 @@ impl MetaFile {
         let mut meta_path_string = asset_path_string.clone();
         meta_path_string.push_str(".meta");
 
-        return (asset_path_string, meta_path_string);
+        (asset_path_string, meta_path_string)
     }
 
     /// Returns the asset and meta file paths
     pub fn get_paths(&self) -> (String, String) {
-        return self.get_paths_stem(&self.directory);
+        self.get_paths_stem(&self.directory)
     }
 }
@@ -0,0 +1,210 @@
+// ===================================================================================
+//  BSD 3-Clause License
+//
+//  Copyright (c) 2023-2024, Liam R. (zCubed3)
+//
+//  Redistribution and use in source and binary forms, with or without
+//  modification, are permitted provided that the following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this
+//     list of conditions and the following disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice,
+//     this list of conditions and the following disclaimer in the documentation
+//     and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its
+//     contributors may be used to endorse or promote products derived from
+//     this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+//  AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+//  IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+//  FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+//  DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+//  CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+//  OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+//  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ===================================================================================
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::meta_file::MetaFile;
+
+/// How much each property of a candidate counts towards its score
+const PATH_WEIGHT: f32 = 0.5;
+const IMPORTER_WEIGHT: f32 = 0.3;
+const SIZE_WEIGHT: f32 = 0.2;
+
+/// The destination asset picked for a source asset with the same file name
+#[derive(Debug, Clone)]
+pub struct NameMatch {
+    pub dst_meta: MetaFile,
+
+    /// How likely this is the same asset, from 0 to 1
+    pub score: f32,
+
+    /// Every other candidate with its score, best first
+    pub alternatives: Vec<(MetaFile, f32)>,
+}
+
+/// Finds destination assets sharing a path or file name with source assets
+///
+/// Paths are compared relative to a root on each side (ex. CustomItemsSDK and MarrowSDK),
+/// an asset at the same relative path always wins. Otherwise several destination assets
+/// may share a name, so candidates are scored by how similar their folders, importers and
+/// sizes are
+pub struct NameMatcher<'a> {
+    src_root: PathBuf,
+    dst_root: PathBuf,
+    by_path: HashMap<String, &'a MetaFile>,
+    by_name: HashMap<u64, Vec<&'a MetaFile>>,
+}
+
+impl<'a> NameMatcher<'a> {
+    pub fn new(src_root: PathBuf, dst_root: PathBuf, dst_metas: &'a [MetaFile]) -> Self {
+        let mut by_path = HashMap::<String, &MetaFile>::new();
+        let mut by_name = HashMap::<u64, Vec<&MetaFile>>::new();
+
+        for dst_meta in dst_metas {
+            if let Some(path) = relative_path(&dst_root, dst_meta) {
+                by_path.entry(path).or_insert(dst_meta);
+            }
+
+            by_name
+                .entry(dst_meta.base_hash)
+                .or_default()
+                .push(dst_meta);
+        }
+
+        Self {
+            src_root,
+            dst_root,
+            by_path,
+            by_name,
+        }
+    }
+
+    /// Returns the destination asset at the same path relative to the roots
+    pub fn path_match(&self, src_meta: &MetaFile) -> Option<&'a MetaFile> {
+        let path = relative_path(&self.src_root, src_meta)?;
+        self.by_path.get(&path).copied()
+    }
+
+    /// Returns the best destination asset with the same path or name, if there are any
+    ///
+    /// Ties go to the candidate that was collected first, so results never change between runs
+    pub fn best_match(&self, src_meta: &MetaFile) -> Option<NameMatch> {
+        if let Some(dst_meta) = self.path_match(src_meta) {
+            return Some(NameMatch {
+                dst_meta: dst_meta.clone(),
+                score: 1.0,
+                alternatives: Vec::new(),
+            });
+        }
+
+        let candidates = self.by_name.get(&src_meta.base_hash)?;
+
+        let src_dirs = relative_dirs(&self.src_root, &src_meta.directory);
+        let src_size = src_meta.asset_stamp().map(|(_, size)| size);
+
+        let mut scored: Vec<(MetaFile, f32)> = candidates
+            .iter()
+            .filter(|dst_meta| dst_meta.base_name == src_meta.base_name)
+            .map(|dst_meta| {
+                let dst_dirs = relative_dirs(&self.dst_root, &dst_meta.directory);
+                let dst_size = dst_meta.asset_stamp().map(|(_, size)| size);
+
+                let score = PATH_WEIGHT * path_similarity(&src_dirs, &dst_dirs)
+                    + IMPORTER_WEIGHT * importer_similarity(&src_meta.importer, &dst_meta.importer)
+                    + SIZE_WEIGHT * size_similarity(src_size, dst_size);
+
+                ((*dst_meta).clone(), score)
+            })
+            .collect();
+
+        // Stable sort, equal scores keep their collection order
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let mut scored = scored.into_iter();
+        let (dst_meta, score) = scored.next()?;
+
+        Some(NameMatch {
+            dst_meta,
+            score,
+            alternatives: scored.collect(),
+        })
+    }
+}
+
+/// Returns the (lowercase) path of an asset relative to a root, None if it's outside of it
+fn relative_path(root: &Path, meta: &MetaFile) -> Option<String> {
+    let asset_path = PathBuf::from(meta.get_paths().0);
+    let relative = asset_path.strip_prefix(root).ok()?;
+
+    let components: Vec<String> = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_lowercase())
+        .collect();
+
+    Some(components.join("/"))
+}
+
+/// Returns the folders between the root and a directory
+fn relative_dirs(root: &Path, directory: &str) -> Vec<String> {
+    let directory = Path::new(directory);
+    let relative = directory.strip_prefix(root).unwrap_or(directory);
+
+    relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_lowercase())
+        .collect()
+}
+
+/// Fraction of folders (counted from the asset upwards) that both paths share
+fn path_similarity(src_dirs: &[String], dst_dirs: &[String]) -> f32 {
+    let depth = src_dirs.len().max(dst_dirs.len());
+
+    if depth == 0 {
+        return 1.0;
+    }
+
+    let shared = src_dirs
+        .iter()
+        .rev()
+        .zip(dst_dirs.iter().rev())
+        .take_while(|(src, dst)| src == dst)
+        .count();
+
+    shared as f32 / depth as f32
+}
+
+fn importer_similarity(src_importer: &str, dst_importer: &str) -> f32 {
+    if src_importer.is_empty() || dst_importer.is_empty() {
+        0.5
+    } else if src_importer == dst_importer {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// Ratio of the smaller size to the larger one, folders count as identical
+fn size_similarity(src_size: Option<u64>, dst_size: Option<u64>) -> f32 {
+    match (src_size, dst_size) {
+        (Some(src_size), Some(dst_size)) => {
+            let larger = src_size.max(dst_size);
+
+            if larger == 0 {
+                1.0
+            } else {
+                src_size.min(dst_size) as f32 / larger as f32
+            }
+        }
+        (None, None) => 1.0,
+        _ => 0.0,
+    }
+}
@@ -0,0 +1,213 @@
+// ===================================================================================
+//  BSD 3-Clause License
+//
+//  Copyright (c) 2023-2024, Liam R. (zCubed3)
+//
+//  Redistribution and use in source and binary forms, with or without
+//  modification, are permitted provided that the following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this
+//     list of conditions and the following disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice,
+//     this list of conditions and the following disclaimer in the documentation
+//     and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its
+//     contributors may be used to endorse or promote products derived from
+//     this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+//  AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+//  IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+//  FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+//  DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+//  CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+//  OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+//  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ===================================================================================
+
+use crate::fresh_guid::GuidMode;
+use crate::graph::GraphFormat;
+use crate::relocation::assets_relative_parts;
+
+/// Command line options, flags may appear anywhere between the positional arguments
+#[derive(Debug)]
+pub struct Options {
+    /// Every argument that isn't a flag (project paths, seeds, etc)
+    pub positional: Vec<String>,
+
+    /// Whether collected meta files are cached between runs
+    pub use_cache: bool,
+
+    /// How many threads are used for collecting and converting
+    pub threads: usize,
+
+    /// Whether collection stops at the first directory or meta file it can't read
+    pub strict: bool,
+
+    /// Whether copied assets keep their GUIDs or get new ones
+    pub guid_mode: GuidMode,
+
+    /// Whether assets sharing a GUID are also compared by their contents
+    pub verify_contents: bool,
+
+    /// Whether source assets colliding with a different destination asset get new GUIDs
+    pub regenerate_collisions: bool,
+
+    /// Name matches scoring below this (0 to 1) are copied instead of remapped
+    pub match_threshold: f32,
+
+    /// Source folder (relative to the assets) that paths are matched from
+    pub src_root: String,
+
+    /// Destination folder (relative to the assets) that paths are matched against
+    pub dst_root: String,
+
+    /// Whether assets without an exact match may be matched by similar names
+    pub fuzzy: bool,
+
+    /// How many characters fuzzy matched names may differ by
+    pub fuzzy_distance: usize,
+
+    /// Whether packages the migrated assets need are added to the destination manifest
+    pub add_packages: bool,
+
+    /// The format the graph command exports to
+    pub graph_format: GraphFormat,
+
+    /// Where the graph command writes to, defaults to ./dependency_graph.<extension>
+    pub output: Option<String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            positional: Vec::new(),
+            use_cache: true,
+            threads: std::thread::available_parallelism()
+                .map(|count| count.get())
+                .unwrap_or(1),
+            strict: false,
+            guid_mode: GuidMode::Keep,
+            verify_contents: false,
+            regenerate_collisions: false,
+            // Above the importer and size weights, a match has to share a folder to count
+            match_threshold: 0.6,
+            src_root: String::new(),
+            dst_root: String::new(),
+            fuzzy: false,
+            fuzzy_distance: 2,
+            add_packages: false,
+            graph_format: GraphFormat::Dot,
+            output: None,
+        }
+    }
+}
+
+impl Options {
+    /// Parses the given arguments (excluding the executable path)
+    pub fn parse<I: Iterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut options = Self::default();
+
+        let mut args = args.peekable();
+
+        while let Some(arg) = args.next() {
+            let Some(flag) = arg.strip_prefix("--") else {
+                options.positional.push(arg);
+                continue;
+            };
+
+            // Values can be given as either "--flag value" or "--flag=value"
+            let (flag, inline_value) = match flag.split_once('=') {
+                Some((flag, value)) => (flag, Some(value.to_string())),
+                None => (flag, None),
+            };
+
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("Option --{} expects a value", flag))
+            };
+
+            match flag {
+                "no-cache" => options.use_cache = false,
+                "strict" => options.strict = true,
+                "verify-contents" => options.verify_contents = true,
+                "regenerate-collisions" => options.regenerate_collisions = true,
+                "fresh-guids" => options.guid_mode = GuidMode::parse(&value()?),
+                "threads" => {
+                    options.threads = value()?
+                        .parse()
+                        .ok()
+                        .filter(|threads| *threads > 0)
+                        .ok_or("Option --threads expects a number above zero")?;
+                }
+                "src-root" => options.src_root = assets_relative(&value()?),
+                "dst-root" => options.dst_root = assets_relative(&value()?),
+                "fuzzy" => options.fuzzy = true,
+                "add-packages" => options.add_packages = true,
+                "format" => {
+                    options.graph_format = GraphFormat::parse(&value()?)
+                        .ok_or("Option --format expects dot, graphml or json")?;
+                }
+                "output" => options.output = Some(value()?),
+                "fuzzy-distance" => {
+                    options.fuzzy = true;
+                    options.fuzzy_distance = value()?
+                        .parse()
+                        .map_err(|_| "Option --fuzzy-distance expects a number")?;
+                }
+                "match-threshold" => {
+                    options.match_threshold = value()?
+                        .parse()
+                        .ok()
+                        .filter(|threshold| (0.0..=1.0).contains(threshold))
+                        .ok_or("Option --match-threshold expects a number from 0 to 1")?;
+                }
+                _ => return Err(format!("Unknown option --{}", flag)),
+            }
+        }
+
+        Ok(options)
+    }
+
+    pub fn print_help() {
+        println!("\nOptions:");
+        println!("\t--no-cache\t\tDon't read or write the meta file cache");
+        println!(
+            "\t--threads [count]\tThreads used for collection and conversion (default: all cores)"
+        );
+        println!("\t--strict\t\tStop if any directory or meta file can't be read");
+        println!("\t--fresh-guids [random|seed]\tGive copied assets new GUIDs (random or seeded by path)");
+        println!("\t--src-root [folder]\tSource folder that asset paths are matched from");
+        println!("\t--dst-root [folder]\tDestination folder that asset paths are matched against");
+        println!("\t--match-threshold [0-1]\tCopy assets whose best name match scores lower (default: 0.6)");
+        println!(
+            "\t--fuzzy\t\t\tMatch similar names (see extension_classes.txt) with the same importer"
+        );
+        println!(
+            "\t--fuzzy-distance [n]\tCharacters fuzzy matched names may differ by (default: 2)"
+        );
+        println!(
+            "\t--add-packages\t\tAdd packages the migrated assets need to the destination manifest"
+        );
+        println!("\t--verify-contents\tAlso compare contents of assets sharing a GUID");
+        println!(
+            "\t--regenerate-collisions\tGive source assets colliding with another asset new GUIDs"
+        );
+        println!(
+            "\t--format [dot|graphml|json]\tFormat of the graph command's output (default: dot)"
+        );
+        println!("\t--output [path]\t\tWhere the graph command writes to");
+    }
+}
+
+/// Turns a folder given on the command line into a path relative to the assets folder,
+/// a leading `Assets/` is ignored like in the other config files
+fn assets_relative(path: &str) -> String {
+    assets_relative_parts(path).join("/")
+}
@@ -0,0 +1,207 @@
+// ===================================================================================
+//  BSD 3-Clause License
+//
+//  Copyright (c) 2023-2024, Liam R. (zCubed3)
+//
+//  Redistribution and use in source and binary forms, with or without
+//  modification, are permitted provided that the following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this
+//     list of conditions and the following disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice,
+//     this list of conditions and the following disclaimer in the documentation
+//     and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its
+//     contributors may be used to endorse or promote products derived from
+//     this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+//  AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+//  IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+//  FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+//  DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+//  CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+//  OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+//  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ===================================================================================
+
+use std::collections::HashMap;
+use std::fs::{read_dir, read_to_string};
+use std::path::{Path, PathBuf};
+
+use crate::json::JsonValue;
+use crate::manifest::PackageManifest;
+use crate::meta_file::{collect_meta_files, save_meta_files, MetaFile};
+use crate::options::Options;
+
+/// GUIDs of Unity's built-in resources (builtin_extra and unity default resources)
+pub const BUILTIN_GUIDS: [&str; 2] = [
+    "0000000000000000e000000000000000",
+    "0000000000000000f000000000000000",
+];
+
+pub fn is_builtin_guid(guid: &str) -> bool {
+    BUILTIN_GUIDS.contains(&guid)
+}
+
+/// Returns the project folder owning an assets folder
+pub fn project_root(assets: &str) -> Option<&Path> {
+    Path::new(assets).parent()
+}
+
+/// A UPM package found in a project
+#[derive(Debug, Clone)]
+pub struct Package {
+    /// The package name (ex. com.unity.render-pipelines.universal)
+    pub name: String,
+
+    pub version: String,
+
+    /// The folder containing the package's package.json
+    pub path: PathBuf,
+
+    /// Whether the package is embedded in Packages/ or a local (file:) package, rather than
+    /// one installed from a registry into the package cache
+    pub embedded: bool,
+}
+
+/// Every package of a project along with the GUIDs of their assets
+#[derive(Debug, Default)]
+pub struct PackageIndex {
+    pub packages: Vec<Package>,
+
+    /// Index into `packages` for each GUID
+    pub guids: HashMap<String, usize>,
+
+    /// Meta files of embedded and local packages, these are migrated like any other asset
+    pub embedded_metas: Vec<MetaFile>,
+}
+
+impl PackageIndex {
+    /// Collects the packages of the project owning an assets folder
+    ///
+    /// Embedded packages (Packages/) come before local ones (file: dependencies) and cached
+    /// ones (Library/PackageCache), if a package is in several places the first wins just
+    /// like in Unity
+    pub fn collect(assets: &str, options: &Options) -> Self {
+        let mut index = Self::default();
+
+        let Some(project) = project_root(assets) else {
+            return index;
+        };
+
+        let sources = [
+            (package_folders(&project.join("Packages")), true),
+            (local_package_folders(assets), true),
+            (
+                package_folders(&project.join("Library").join("PackageCache")),
+                false,
+            ),
+        ];
+
+        for (folders, embedded) in sources {
+            for folder in folders {
+                let Some(package) = read_package(&folder, embedded) else {
+                    continue;
+                };
+
+                if index.find_by_name(&package.name).is_some() {
+                    continue;
+                }
+
+                let folder_path = folder.display().to_string();
+
+                let Some(metas) = collect_meta_files(&folder_path, options) else {
+                    println!("[Packages]: Collection of {:?} was stopped", package.name);
+                    continue;
+                };
+
+                save_meta_files(&folder_path, &metas, options);
+
+                let package_index = index.packages.len();
+
+                for meta in &metas {
+                    index
+                        .guids
+                        .entry(meta.guid.clone())
+                        .or_insert(package_index);
+                }
+
+                if embedded {
+                    index.embedded_metas.extend(metas);
+                }
+
+                index.packages.push(package);
+            }
+        }
+
+        index
+    }
+
+    /// Returns the package containing an asset
+    pub fn find(&self, guid: &str) -> Option<&Package> {
+        self.guids.get(guid).map(|index| &self.packages[*index])
+    }
+
+    pub fn find_by_name(&self, name: &str) -> Option<&Package> {
+        self.packages.iter().find(|package| package.name == name)
+    }
+}
+
+/// Returns every package folder within a folder, sorted
+fn package_folders(root: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = read_dir(root) else {
+        return Vec::new();
+    };
+
+    let mut folders: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.join("package.json").is_file())
+        .collect();
+
+    folders.sort();
+    folders
+}
+
+/// Returns the folders of local packages (file: dependencies) in a project's manifest
+fn local_package_folders(assets: &str) -> Vec<PathBuf> {
+    let Some(manifest) = PackageManifest::load(assets) else {
+        return Vec::new();
+    };
+
+    let Some(JsonValue::Object(dependencies)) = manifest.json.get("dependencies") else {
+        return Vec::new();
+    };
+
+    // Local paths are relative to the Packages folder
+    let packages_folder = manifest.path.parent().unwrap_or(Path::new(""));
+
+    dependencies
+        .iter()
+        .filter_map(|(_, version)| version.as_str()?.strip_prefix("file:"))
+        .map(|path| packages_folder.join(path))
+        .filter(|path| path.join("package.json").is_file())
+        .collect()
+}
+
+/// Reads the name and version of a package from its package.json
+fn read_package(folder: &Path, embedded: bool) -> Option<Package> {
+    let contents = read_to_string(folder.join("package.json")).ok()?;
+    let json = JsonValue::parse(&contents)?;
+
+    Some(Package {
+        name: json.get("name")?.as_str()?.to_string(),
+        version: json
+            .get("version")
+            .and_then(JsonValue::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        path: folder.to_path_buf(),
+        embedded,
+    })
+}
@@ -0,0 +1,362 @@
+// ===================================================================================
+//  BSD 3-Clause License
+//
+//  Copyright (c) 2023-2024, Liam R. (zCubed3)
+//
+//  Redistribution and use in source and binary forms, with or without
+//  modification, are permitted provided that the following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this
+//     list of conditions and the following disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice,
+//     this list of conditions and the following disclaimer in the documentation
+//     and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its
+//     contributors may be used to endorse or promote products derived from
+//     this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+//  AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+//  IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+//  FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+//  DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+//  CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+//  OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+//  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ===================================================================================
+
+use std::fs::{read_to_string, write, File};
+use std::io;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::asset_format::AssetFormat;
+use crate::rewrite::is_guid;
+
+/// Why a single asset was migrated
+#[derive(Debug, Clone)]
+pub struct Provenance {
+    pub guid: String,
+
+    /// The project path of the asset within the source project (ex. Assets/Icon.png)
+    pub path: String,
+
+    /// The GUID of the asset that first referenced this one, None for seeds
+    pub referrer: Option<String>,
+
+    /// Where the reference appears within the referrer
+    /// (ex. Material &2100000: m_SavedProperties.m_TexEnvs._MainTex.m_Texture)
+    pub field: Option<String>,
+}
+
+/// The provenance of every asset of a migration, written next to the other run files
+///
+/// Each line is tab separated: GUID, path, referrer GUID and field ("-" when there are none)
+#[derive(Debug, Default)]
+pub struct ProvenanceReport {
+    pub entries: Vec<Provenance>,
+}
+
+impl ProvenanceReport {
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let contents = read_to_string(path).ok()?;
+        let mut report = Self::default();
+
+        for line in contents.lines() {
+            if line.starts_with('#') || line.is_empty() {
+                continue;
+            }
+
+            let columns: Vec<&str> = line.split('\t').collect();
+
+            let [guid, path, referrer, field] = columns[..] else {
+                println!("[Report]: Skipping malformed line {:?}", line);
+                continue;
+            };
+
+            let optional = |value: &str| (value != "-").then(|| value.to_string());
+
+            report.entries.push(Provenance {
+                guid: guid.to_string(),
+                path: path.to_string(),
+                referrer: optional(referrer),
+                field: optional(field),
+            });
+        }
+
+        Some(report)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut contents = String::from("# guid\tpath\treferrer\tfield\n");
+
+        for entry in &self.entries {
+            contents.push_str(&format!(
+                "{}\t{}\t{}\t{}\n",
+                entry.guid,
+                entry.path,
+                entry.referrer.as_deref().unwrap_or("-"),
+                entry.field.as_deref().unwrap_or("-")
+            ));
+        }
+
+        write(path, contents)
+    }
+
+    /// Finds an asset by GUID or by path (relative to the project or the assets folder)
+    pub fn find(&self, query: &str) -> Option<&Provenance> {
+        if is_guid(query.as_bytes()) {
+            let guid = query.to_lowercase();
+            return self.entries.iter().find(|entry| entry.guid == guid);
+        }
+
+        let query = query.replace('\\', "/");
+        let in_assets = format!("Assets/{}", query);
+
+        self.entries
+            .iter()
+            .find(|entry| entry.path == query || entry.path == in_assets)
+    }
+
+    /// Returns the reference chain of an asset, starting at the seed that pulled it in
+    pub fn chain<'a>(&'a self, entry: &'a Provenance) -> Vec<&'a Provenance> {
+        let mut chain = vec![entry];
+
+        while let Some(referrer) = &chain.last().unwrap().referrer {
+            // A cycle can't happen (assets are only claimed once) but a hand edited report might
+            match self.entries.iter().find(|entry| &entry.guid == referrer) {
+                Some(entry) if !chain.iter().any(|other| other.guid == entry.guid) => {
+                    chain.push(entry)
+                }
+                _ => break,
+            }
+        }
+
+        chain.reverse();
+        chain
+    }
+}
+
+/// Where a reference was found, recorded when the reference is resolved
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReferenceSource {
+    /// The asset itself, serialized in the given format
+    Asset(AssetFormat),
+
+    /// The asset's meta file (ex. material remaps of models)
+    Meta,
+}
+
+/// Describes where a GUID is referenced within an asset
+///
+/// Text serialized assets and meta files name the chain of keys leading to the reference,
+/// prefixed by the object it's in (ex. Material &2100000: m_SavedProperties.m_TexEnvs).
+/// Other formats don't have fields like these, so they're described by what holds the reference
+pub fn describe_reference(asset_path: &Path, guid: &str, source: ReferenceSource) -> String {
+    match source {
+        ReferenceSource::Asset(AssetFormat::Yaml) => locate_yaml_reference(asset_path, guid, "")
+            .unwrap_or_else(|| String::from("a YAML reference")),
+        ReferenceSource::Meta => {
+            let meta_path = format!("{}.meta", asset_path.display());
+
+            locate_yaml_reference(Path::new(&meta_path), guid, " (meta)")
+                .unwrap_or_else(|| String::from("the importer settings (meta)"))
+        }
+        ReferenceSource::Asset(AssetFormat::Json) => String::from("a JSON \"guid\" field"),
+        ReferenceSource::Asset(AssetFormat::Assembly) => String::from("an assembly reference"),
+        ReferenceSource::Asset(AssetFormat::UiToolkit) => String::from("a project:// URL"),
+        ReferenceSource::Asset(AssetFormat::Binary) => {
+            String::from("the external references table (binary)")
+        }
+        ReferenceSource::Asset(AssetFormat::Opaque) => String::from("the asset's contents"),
+    }
+}
+
+/// Object names are suffixed by the label (ex. ModelImporter (meta))
+fn locate_yaml_reference(path: &Path, guid: &str, label: &str) -> Option<String> {
+    let reader = BufReader::new(File::open(path).ok()?);
+
+    // Keys of the mappings the current line is nested in, along with their indentation
+    let mut keys = Vec::<(usize, String)>::new();
+    let mut anchor = String::new();
+
+    for line in reader.lines().map_while(Result::ok) {
+        // Documents start with "--- !u!<class id> &<file id>", the file id names the object
+        if let Some(header) = line.strip_prefix("--- ") {
+            anchor = header
+                .split_whitespace()
+                .find(|part| part.starts_with('&'))
+                .unwrap_or_default()
+                .to_string();
+
+            keys.clear();
+            continue;
+        }
+
+        let content = line.trim_start();
+        let mut indent = line.len() - content.len();
+        let mut content = content;
+
+        // List items are nested one level deeper than the key owning the list
+        while let Some(item) = content.strip_prefix("- ") {
+            indent += 2;
+            content = item;
+        }
+
+        let key = content
+            .split_once(':')
+            .map(|(key, _)| key)
+            .filter(|key| !key.is_empty() && !key.starts_with(['{', '"', '%']))
+            .filter(|key| !key.contains([',', ' ']) && *key != "guid");
+
+        if key.is_some() || !content.is_empty() {
+            while keys.last().is_some_and(|(depth, _)| *depth >= indent) {
+                keys.pop();
+            }
+        }
+
+        if let Some(key) = key {
+            keys.push((indent, key.to_string()));
+        }
+
+        if !line.contains(guid) {
+            continue;
+        }
+
+        let mut keys = keys.iter().map(|(_, key)| key.as_str());
+        let object = format!("{}{}", keys.next()?, label);
+        let field = keys.collect::<Vec<&str>>().join(".");
+
+        return Some(match (anchor.is_empty(), field.is_empty()) {
+            (true, true) => object,
+            (true, false) => format!("{}: {}", object, field),
+            (false, true) => format!("{} {}", object, anchor),
+            (false, false) => format!("{} {}: {}", object, anchor, field),
+        });
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, remove_dir_all};
+
+    const TEXTURE: &str = "5555555555555555555555555555555c";
+    const SHADER: &str = "66666666666666666666666666666660";
+
+    const MATERIAL: &str = "%YAML 1.1
+%TAG !u! tag:unity3d.com,2011:
+--- !u!21 &2100000
+Material:
+  m_Name: Steel
+  m_Shader: {fileID: 4800000, guid: 66666666666666666666666666666660, type: 3}
+  m_SavedProperties:
+    m_TexEnvs:
+    - _BumpMap:
+        m_Texture: {fileID: 0}
+    - _MainTex:
+        m_Texture: {fileID: 2800000, guid: 5555555555555555555555555555555c,
+          type: 3}
+        m_Scale: {x: 1, y: 1}
+";
+
+    fn entry(guid: &str, path: &str, referrer: Option<&str>) -> Provenance {
+        Provenance {
+            guid: guid.to_string(),
+            path: path.to_string(),
+            referrer: referrer.map(String::from),
+            field: referrer.map(|_| String::from("MonoBehaviour &300: data")),
+        }
+    }
+
+    #[test]
+    fn yaml_references_are_located_by_their_keys() {
+        let root = std::env::temp_dir().join(format!("provenance_{}", std::process::id()));
+        create_dir_all(&root).unwrap();
+
+        let path = root.join("Steel.mat");
+        write(&path, MATERIAL).unwrap();
+        write(
+            root.join("Steel.mat.meta"),
+            format!("fileFormatVersion: 2\nguid: 99999999999999999999999999999991\nNativeFormatImporter:\n  mainObject: {{fileID: 0, guid: {}, type: 3}}\n", TEXTURE),
+        )
+        .unwrap();
+
+        let texture = describe_reference(&path, TEXTURE, ReferenceSource::Asset(AssetFormat::Yaml));
+        let shader = describe_reference(&path, SHADER, ReferenceSource::Asset(AssetFormat::Yaml));
+        let meta = describe_reference(&path, TEXTURE, ReferenceSource::Meta);
+        let json = describe_reference(&path, TEXTURE, ReferenceSource::Asset(AssetFormat::Json));
+
+        let _ = remove_dir_all(&root);
+
+        assert_eq!(
+            texture,
+            "Material &2100000: m_SavedProperties.m_TexEnvs._MainTex.m_Texture"
+        );
+        assert_eq!(shader, "Material &2100000: m_Shader");
+        assert_eq!(meta, "NativeFormatImporter (meta): mainObject");
+        assert_eq!(json, "a JSON \"guid\" field");
+    }
+
+    #[test]
+    fn chains_start_at_the_seed() {
+        let report = ProvenanceReport {
+            entries: vec![
+                entry("a", "Assets/Thing.prefab", None),
+                entry("b", "Assets/Steel.mat", Some("a")),
+                entry("c", "Assets/steel.png", Some("b")),
+            ],
+        };
+
+        let found = report.find("steel.png").unwrap();
+        let chain: Vec<&str> = report
+            .chain(found)
+            .iter()
+            .map(|e| e.guid.as_str())
+            .collect();
+
+        assert_eq!(chain, ["a", "b", "c"]);
+        assert!(report.find("Assets/Steel.mat").is_some());
+        assert!(report.find("Missing.png").is_none());
+    }
+
+    #[test]
+    fn reports_survive_a_round_trip() {
+        let report = ProvenanceReport {
+            entries: vec![
+                entry(
+                    "11111111111111111111111111111111",
+                    "Assets/Thing.prefab",
+                    None,
+                ),
+                entry(
+                    TEXTURE,
+                    "Assets/steel.png",
+                    Some("11111111111111111111111111111111"),
+                ),
+            ],
+        };
+
+        let path = std::env::temp_dir().join(format!("report_{}.txt", std::process::id()));
+        report.save(&path).unwrap();
+
+        let loaded = ProvenanceReport::load_from_file(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(loaded.entries.len(), 2);
+        assert_eq!(loaded.entries[0].referrer, None);
+        assert_eq!(loaded.entries[0].field, None);
+        assert_eq!(loaded.entries[1].referrer, report.entries[1].referrer);
+        assert_eq!(loaded.entries[1].field, report.entries[1].field);
+        assert_eq!(
+            report.find(&TEXTURE.to_uppercase()).unwrap().path,
+            "Assets/steel.png"
+        );
+    }
+}
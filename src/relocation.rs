@@ -0,0 +1,176 @@
+// ===================================================================================
+//  BSD 3-Clause License
+//
+//  Copyright (c) 2023-2024, Liam R. (zCubed3)
+//
+//  Redistribution and use in source and binary forms, with or without
+//  modification, are permitted provided that the following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this
+//     list of conditions and the following disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice,
+//     this list of conditions and the following disclaimer in the documentation
+//     and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its
+//     contributors may be used to endorse or promote products derived from
+//     this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+//  AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+//  IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+//  FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+//  DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+//  CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+//  OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+//  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ===================================================================================
+
+use std::fmt;
+use std::fs::read_to_string;
+use std::path::{Component, Path, PathBuf};
+
+/// Splits a path from a config file or the command line into parts relative to the
+/// assets folder
+///
+/// Both slashes separate parts, a leading `Assets` (in any casing) is ignored and so is
+/// a trailing `**`, every place that takes asset paths goes through this
+pub fn assets_relative_parts(path: &str) -> Vec<String> {
+    let mut parts: Vec<String> = path
+        .trim()
+        .split(['/', '\\'])
+        .filter(|part| !part.is_empty() && *part != ".")
+        .map(String::from)
+        .collect();
+
+    if parts
+        .first()
+        .is_some_and(|part| part.eq_ignore_ascii_case("Assets"))
+    {
+        parts.remove(0);
+    }
+
+    if parts.last().is_some_and(|part| part == "**") {
+        parts.pop();
+    }
+
+    parts
+}
+
+/// A single `from -> to` path relocation rule
+///
+/// Both sides are paths relative to the assets folder, a leading `Assets/` is ignored
+/// A trailing `/**` is optional and just means "this folder and everything under it"
+/// Any component may be `*` to match exactly one folder of any name
+#[derive(Debug, Clone)]
+pub struct RelocationRule {
+    pub from: Vec<String>,
+    pub to: Vec<String>,
+}
+
+impl RelocationRule {
+    /// Parses a `from -> to` rule, returns None if the line is malformed
+    pub fn parse(line: &str) -> Option<Self> {
+        let (from, to) = line.split_once("->")?;
+
+        Some(Self {
+            from: assets_relative_parts(from),
+            to: assets_relative_parts(to),
+        })
+    }
+
+    /// Returns the relocated path if this rule matches the given relative path
+    pub fn apply(&self, relative: &[String]) -> Option<PathBuf> {
+        if relative.len() < self.from.len() {
+            return None;
+        }
+
+        for (pattern, part) in self.from.iter().zip(relative) {
+            if pattern != "*" && pattern != part {
+                return None;
+            }
+        }
+
+        let mut path = PathBuf::new();
+
+        for part in self.to.iter().chain(&relative[self.from.len()..]) {
+            path.push(part);
+        }
+
+        Some(path)
+    }
+}
+
+impl fmt::Display for RelocationRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |parts: &Vec<String>| {
+            let mut joined = parts.join("/");
+
+            if !joined.is_empty() {
+                joined.push('/');
+            }
+
+            joined.push_str("**");
+            joined
+        };
+
+        write!(f, "{} -> {}", join(&self.from), join(&self.to))
+    }
+}
+
+/// Moves migrated assets into different folders of the destination project
+///
+/// Rules are checked in order and the first match wins, so a `** -> Some/Folder/**` rule
+/// placed last acts as the fallback prefix for anything left unmatched
+#[derive(Debug, Default)]
+pub struct Relocator {
+    pub rules: Vec<RelocationRule>,
+}
+
+impl Relocator {
+    /// Reads rules from a file, lines starting with # are comments
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Self {
+        let mut relocator = Self::default();
+
+        if let Ok(file) = read_to_string(path) {
+            for line in file.lines() {
+                let line = line.trim();
+
+                if line.starts_with('#') || line.is_empty() {
+                    continue;
+                }
+
+                match RelocationRule::parse(line) {
+                    Some(rule) => relocator.rules.push(rule),
+                    None => println!("[Relocation]: Ignoring malformed rule {:?}", line),
+                }
+            }
+        }
+
+        relocator
+    }
+
+    /// Relocates a path relative to the source assets folder
+    pub fn relocate<P: AsRef<Path>>(&self, relative: P) -> PathBuf {
+        let relative = relative.as_ref();
+
+        let parts: Vec<String> = relative
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+                _ => None,
+            })
+            .collect();
+
+        for rule in &self.rules {
+            if let Some(path) = rule.apply(&parts) {
+                return path;
+            }
+        }
+
+        relative.to_path_buf()
+    }
+}
@@ -0,0 +1,207 @@
+// ===================================================================================
+//  BSD 3-Clause License
+//
+//  Copyright (c) 2023-2024, Liam R. (zCubed3)
+//
+//  Redistribution and use in source and binary forms, with or without
+//  modification, are permitted provided that the following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this
+//     list of conditions and the following disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice,
+//     this list of conditions and the following disclaimer in the documentation
+//     and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its
+//     contributors may be used to endorse or promote products derived from
+//     this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+//  AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+//  IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+//  FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+//  DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+//  CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+//  OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+//  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ===================================================================================
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs::read_to_string;
+use std::path::Path;
+
+use crate::meta_file::MetaFile;
+use crate::rewrite::is_guid;
+use crate::source_root::{project_path_in, SourceRoot};
+
+/// What a reference to an overridden source asset becomes
+#[derive(Debug, Clone, PartialEq)]
+pub enum RemapTarget {
+    /// Points the reference at this destination GUID
+    Guid(String),
+
+    /// Leaves the reference untouched (and the asset is never copied)
+    Keep,
+
+    /// Points the reference at nothing
+    Null,
+}
+
+impl Display for RemapTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Guid(guid) => write!(f, "{}", guid),
+            Self::Keep => write!(f, "keep"),
+            Self::Null => write!(f, "null"),
+        }
+    }
+}
+
+/// A manual override of a source GUID, these win over any automatic matching
+#[derive(Debug, Clone)]
+pub struct RemapOverride {
+    /// The source as written in the remap table (a GUID or path)
+    pub source: String,
+
+    /// The target as written in the remap table
+    pub target_name: String,
+
+    pub target: RemapTarget,
+}
+
+impl Display for RemapOverride {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} -> {}", self.source, self.target_name)
+    }
+}
+
+/// Explicit `source, target` remaps loaded from a CSV file
+///
+/// Sources are GUIDs or paths in the source project, targets are GUIDs or paths in the
+/// destination project, `keep` (leave the reference as-is) or `null` (null it out)
+#[derive(Debug, Default)]
+pub struct RemapTable {
+    pub rows: Vec<(String, String)>,
+}
+
+impl RemapTable {
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Self {
+        let mut table = Self::default();
+
+        if let Ok(file) = read_to_string(path) {
+            for line in file.lines() {
+                let line = line.trim();
+
+                if line.starts_with('#') || line.is_empty() {
+                    continue;
+                }
+
+                let columns = line.split_once(',').map(|(source, target)| {
+                    (unquote(source).to_string(), unquote(target).to_string())
+                });
+
+                match columns {
+                    Some((source, target)) if !source.is_empty() && !target.is_empty() => {
+                        table.rows.push((source, target))
+                    }
+                    _ => println!("[Remap]: Ignoring malformed line {:?}", line),
+                }
+            }
+        }
+
+        table
+    }
+
+    /// Turns every row into an override keyed by source GUID
+    ///
+    /// Paths are looked up in the collected meta files of every root (the assets folder and
+    /// embedded packages), rows naming unknown paths are reported and skipped
+    pub fn resolve(
+        &self,
+        src_roots: &[SourceRoot],
+        src_metas: &[MetaFile],
+        dst_roots: &[SourceRoot],
+        dst_metas: &[MetaFile],
+    ) -> HashMap<String, RemapOverride> {
+        let src_paths = path_index(src_roots, src_metas);
+        let dst_paths = path_index(dst_roots, dst_metas);
+
+        let mut overrides = HashMap::<String, RemapOverride>::new();
+
+        for (source, target_name) in &self.rows {
+            let Some(guid) = lookup(source, &src_paths) else {
+                println!("[Remap]: Ignoring {:?}, no such source asset", source);
+                continue;
+            };
+
+            let target = match target_name.to_lowercase().as_str() {
+                "keep" => RemapTarget::Keep,
+                "null" => RemapTarget::Null,
+                _ => match lookup(target_name, &dst_paths) {
+                    Some(guid) => RemapTarget::Guid(guid),
+                    None => {
+                        println!(
+                            "[Remap]: Ignoring {:?}, no such destination asset",
+                            target_name
+                        );
+                        continue;
+                    }
+                },
+            };
+
+            overrides.insert(
+                guid,
+                RemapOverride {
+                    source: source.clone(),
+                    target_name: target_name.clone(),
+                    target,
+                },
+            );
+        }
+
+        overrides
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+}
+
+fn unquote(column: &str) -> &str {
+    let column = column.trim();
+
+    column
+        .strip_prefix('"')
+        .and_then(|column| column.strip_suffix('"'))
+        .unwrap_or(column)
+}
+
+/// Maps lowercase project paths (ex. assets/icon.png) to GUIDs
+fn path_index(roots: &[SourceRoot], metas: &[MetaFile]) -> HashMap<String, String> {
+    metas
+        .iter()
+        .filter_map(|meta| {
+            let path = project_path_in(roots, meta)?;
+            Some((path.to_lowercase(), meta.guid.clone()))
+        })
+        .collect()
+}
+
+/// Returns the GUID of a GUID or (optionally Assets/ prefixed) path column
+fn lookup(column: &str, paths: &HashMap<String, String>) -> Option<String> {
+    if is_guid(column.as_bytes()) {
+        return Some(column.to_lowercase());
+    }
+
+    let path = column.replace('\\', "/").to_lowercase();
+    let path = path.trim_start_matches("./").trim_end_matches('/');
+
+    paths
+        .get(path)
+        .or_else(|| paths.get(&format!("assets/{}", path)))
+        .cloned()
+}
@@ -0,0 +1,838 @@
+// ===================================================================================
+//  BSD 3-Clause License
+//
+//  Copyright (c) 2023-2024, Liam R. (zCubed3)
+//
+//  Redistribution and use in source and binary forms, with or without
+//  modification, are permitted provided that the following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this
+//     list of conditions and the following disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice,
+//     this list of conditions and the following disclaimer in the documentation
+//     and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its
+//     contributors may be used to endorse or promote products derived from
+//     this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+//  AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+//  IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+//  FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+//  DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+//  CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+//  OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+//  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ===================================================================================
+
+use std::io::{self, Read, Write};
+use std::ops::Range;
+
+/// How much is read from a file at once while rewriting
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Length of a GUID written as text
+pub const GUID_LENGTH: usize = 32;
+
+/// GUID of nothing, references to it are written as empty references where the format allows
+pub const NULL_GUID: &str = "00000000000000000000000000000000";
+
+/// A GUID reference found within a buffer
+#[derive(Debug, Clone)]
+pub struct GuidMatch {
+    /// Where the whole reference begins
+    pub start: usize,
+
+    /// Where the GUID text itself is, this is the part that gets replaced
+    pub guid: Range<usize>,
+
+    /// Where scanning continues after this match
+    pub end: usize,
+
+    /// Where the referenced asset's path is, for references that carry one (ex. UXML)
+    pub path: Option<Range<usize>>,
+
+    /// The whole reference (ex. `{fileID: 2800000, guid: ..., type: 3}`), for formats where
+    /// it can be replaced by an empty reference
+    pub reference: Option<Range<usize>>,
+}
+
+/// Decides what references are rewritten to
+pub trait Resolver {
+    /// Called for every GUID found, returns its replacement (if any)
+    fn resolve(&mut self, guid: &str) -> Option<String>;
+
+    /// Returns the (Assets/ relative) path an asset will have in the destination project
+    fn project_path(&mut self, _guid: &str) -> Option<String> {
+        None
+    }
+}
+
+impl<F: FnMut(&str) -> Option<String>> Resolver for F {
+    fn resolve(&mut self, guid: &str) -> Option<String> {
+        self(guid)
+    }
+}
+
+/// Finds GUID references within a file format
+pub trait ReferenceScanner: Sync {
+    /// The longest a single match can be, chunks overlap by this much while streaming
+    fn max_match_len(&self) -> usize;
+
+    /// Finds the first reference starting at or after `from`
+    ///
+    /// Only references that fit entirely within the buffer may be returned
+    fn find(&self, buf: &[u8], from: usize) -> Option<GuidMatch>;
+}
+
+/// Unity's text serialization, references look like `guid: 0123456789abcdef0123456789abcdef`
+pub struct YamlScanner;
+
+impl ReferenceScanner for YamlScanner {
+    fn max_match_len(&self) -> usize {
+        2 * MAX_FLOW_PART + YAML_PATTERN.len() + GUID_LENGTH
+    }
+
+    fn find(&self, buf: &[u8], from: usize) -> Option<GuidMatch> {
+        let mut scan_from = from;
+
+        while let Some(pattern) = find_bytes(buf, YAML_PATTERN, scan_from) {
+            let guid = pattern + YAML_PATTERN.len()..pattern + YAML_PATTERN.len() + GUID_LENGTH;
+
+            if buf.get(guid.clone()).is_some_and(is_guid) {
+                let reference = Self::flow_reference(buf, from, pattern, guid.end);
+
+                return Some(GuidMatch {
+                    start: reference
+                        .as_ref()
+                        .map_or(pattern, |reference| reference.start),
+                    end: reference
+                        .as_ref()
+                        .map_or(guid.end, |reference| reference.end),
+                    guid,
+                    path: None,
+                    reference,
+                });
+            }
+
+            scan_from = pattern + YAML_PATTERN.len();
+        }
+
+        None
+    }
+}
+
+const YAML_PATTERN: &[u8] = b"guid: ";
+
+/// Longest the parts of a reference before and after the GUID may be
+const MAX_FLOW_PART: usize = 128;
+
+impl YamlScanner {
+    /// Finds the `{fileID: ..., guid: ..., type: ...}` mapping around a GUID, Unity may
+    /// wrap it over several lines
+    fn flow_reference(
+        buf: &[u8],
+        from: usize,
+        pattern: usize,
+        guid_end: usize,
+    ) -> Option<Range<usize>> {
+        let before = &buf[pattern.saturating_sub(MAX_FLOW_PART).max(from)..pattern];
+        let open = before.iter().rposition(|byte| *byte == b'{')?;
+
+        let mut prefix = FlowParser::new(&before[open + 1..]);
+        prefix.key(b"fileID")?;
+        prefix.integer()?;
+        prefix.byte(b',')?;
+        prefix.end()?;
+
+        let after = &buf[guid_end..(guid_end + MAX_FLOW_PART).min(buf.len())];
+
+        let mut suffix = FlowParser::new(after);
+        suffix.byte(b',')?;
+        suffix.key(b"type")?;
+        suffix.integer()?;
+        suffix.byte(b'}')?;
+
+        Some(pattern - before.len() + open..guid_end + suffix.position)
+    }
+}
+
+/// Steps through the simple flow mappings Unity writes references as
+struct FlowParser<'a> {
+    buf: &'a [u8],
+    position: usize,
+}
+
+impl<'a> FlowParser<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, position: 0 }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .buf
+            .get(self.position)
+            .is_some_and(u8::is_ascii_whitespace)
+        {
+            self.position += 1;
+        }
+    }
+
+    fn byte(&mut self, expected: u8) -> Option<()> {
+        self.skip_whitespace();
+
+        if *self.buf.get(self.position)? != expected {
+            return None;
+        }
+
+        self.position += 1;
+        Some(())
+    }
+
+    fn key(&mut self, key: &[u8]) -> Option<()> {
+        self.skip_whitespace();
+
+        if !self.buf[self.position..].starts_with(key) {
+            return None;
+        }
+
+        self.position += key.len();
+        self.byte(b':')
+    }
+
+    fn integer(&mut self) -> Option<()> {
+        self.skip_whitespace();
+
+        if self.buf.get(self.position) == Some(&b'-') {
+            self.position += 1;
+        }
+
+        let digits = self.buf[self.position..]
+            .iter()
+            .take_while(|byte| byte.is_ascii_digit())
+            .count();
+
+        self.position += digits;
+        (digits > 0).then_some(())
+    }
+
+    /// Checks that only whitespace is left
+    fn end(&mut self) -> Option<()> {
+        self.skip_whitespace();
+        (self.position == self.buf.len()).then_some(())
+    }
+}
+
+/// JSON based assets (ShaderGraph, VFX, Input Actions), references look like `"guid": "..."`
+///
+/// ShaderGraph nests JSON inside of JSON strings, so the quotes may be escaped any number of times
+pub struct JsonScanner;
+
+impl ReferenceScanner for JsonScanner {
+    fn max_match_len(&self) -> usize {
+        // Quotes and escapes on both sides of the key and value, plus the GUID
+        4 * (MAX_ESCAPES + 1) + JSON_KEY.len() + 2 * MAX_WHITESPACE + 1 + GUID_LENGTH
+    }
+
+    fn find(&self, buf: &[u8], from: usize) -> Option<GuidMatch> {
+        let mut from = from;
+
+        while let Some(key) = find_bytes(buf, JSON_KEY, from) {
+            from = key + JSON_KEY.len();
+
+            if let Some(found) = Self::match_at(buf, key) {
+                return Some(found);
+            }
+        }
+
+        None
+    }
+}
+
+const JSON_KEY: &[u8] = b"guid";
+
+/// How many backslashes may escape a quote, enough for JSON in JSON in JSON
+const MAX_ESCAPES: usize = 7;
+
+/// How much whitespace may surround the colon
+const MAX_WHITESPACE: usize = 8;
+
+impl JsonScanner {
+    /// Matches `"guid"`, a colon and a quoted GUID around a found key
+    fn match_at(buf: &[u8], key: usize) -> Option<GuidMatch> {
+        // The key must be quoted ("guid" not "m_guid")
+        if key == 0 || buf[key - 1] != b'"' {
+            return None;
+        }
+
+        let start = key - 1 - Self::escapes_before(buf, key - 1);
+
+        let mut position = Self::skip_quote(buf, key + JSON_KEY.len())?;
+        position = Self::skip_whitespace(buf, position);
+
+        if *buf.get(position)? != b':' {
+            return None;
+        }
+
+        position = Self::skip_whitespace(buf, position + 1);
+        position = Self::skip_quote(buf, position)?;
+
+        let guid = position..position + GUID_LENGTH;
+
+        if !buf.get(guid.clone()).is_some_and(is_guid) {
+            return None;
+        }
+
+        let end = Self::skip_quote(buf, guid.end)?;
+
+        Some(GuidMatch {
+            start,
+            guid,
+            end,
+            path: None,
+            reference: None,
+        })
+    }
+
+    /// Skips an (optionally escaped) quote, returns None if there isn't one
+    fn skip_quote(buf: &[u8], position: usize) -> Option<usize> {
+        let mut position = position;
+        let mut escapes = 0;
+
+        while buf.get(position) == Some(&b'\\') && escapes < MAX_ESCAPES {
+            position += 1;
+            escapes += 1;
+        }
+
+        (buf.get(position) == Some(&b'"')).then_some(position + 1)
+    }
+
+    fn skip_whitespace(buf: &[u8], position: usize) -> usize {
+        let mut position = position;
+        let mut skipped = 0;
+
+        while skipped < MAX_WHITESPACE && buf.get(position).is_some_and(u8::is_ascii_whitespace) {
+            position += 1;
+            skipped += 1;
+        }
+
+        position
+    }
+
+    fn escapes_before(buf: &[u8], position: usize) -> usize {
+        buf[..position]
+            .iter()
+            .rev()
+            .take(MAX_ESCAPES)
+            .take_while(|byte| **byte == b'\\')
+            .count()
+    }
+}
+
+/// Assembly definitions (and references), references look like `"GUID:0123456789abcdef..."`
+pub struct AssemblyScanner;
+
+const ASSEMBLY_PATTERN: &[u8] = b"\"GUID:";
+
+impl ReferenceScanner for AssemblyScanner {
+    fn max_match_len(&self) -> usize {
+        ASSEMBLY_PATTERN.len() + GUID_LENGTH + 1
+    }
+
+    fn find(&self, buf: &[u8], from: usize) -> Option<GuidMatch> {
+        let mut from = from;
+
+        while let Some(start) = find_bytes(buf, ASSEMBLY_PATTERN, from) {
+            let guid = start + ASSEMBLY_PATTERN.len()..start + ASSEMBLY_PATTERN.len() + GUID_LENGTH;
+
+            if buf.get(guid.clone()).is_some_and(is_guid) && buf.get(guid.end) == Some(&b'"') {
+                return Some(GuidMatch {
+                    start,
+                    end: guid.end + 1,
+                    guid,
+                    path: None,
+                    reference: None,
+                });
+            }
+
+            from = start + ASSEMBLY_PATTERN.len();
+        }
+
+        None
+    }
+}
+
+/// UI Toolkit documents and style sheets, references are URLs that look like
+/// `project://database/Assets/Icon.png?fileID=2800000&guid=0123456789abcdef...&type=3`
+pub struct ProjectUrlScanner;
+
+const PROJECT_URL_PATTERN: &[u8] = b"project://database/";
+
+/// Longest path (or query) a project URL may have, longer ones are ignored
+const MAX_URL_PART: usize = 1024;
+
+impl ReferenceScanner for ProjectUrlScanner {
+    fn max_match_len(&self) -> usize {
+        PROJECT_URL_PATTERN.len() + 2 * MAX_URL_PART
+    }
+
+    fn find(&self, buf: &[u8], from: usize) -> Option<GuidMatch> {
+        let mut from = from;
+
+        while let Some(start) = find_bytes(buf, PROJECT_URL_PATTERN, from) {
+            from = start + PROJECT_URL_PATTERN.len();
+
+            if let Some(found) = Self::match_at(buf, start) {
+                return Some(found);
+            }
+        }
+
+        None
+    }
+}
+
+impl ProjectUrlScanner {
+    fn match_at(buf: &[u8], start: usize) -> Option<GuidMatch> {
+        let path_start = start + PROJECT_URL_PATTERN.len();
+        let path_end = path_start + Self::url_part_len(buf, path_start, b"?\"')")?;
+
+        // The path has to be followed by a query, anything else isn't a reference
+        if buf[path_end] != b'?' {
+            return None;
+        }
+
+        // Parameters are separated by & (written as &amp; inside of UXML attributes)
+        let query_start = path_end + 1;
+        let query_end = query_start + Self::url_part_len(buf, query_start, b"#\"')")?;
+
+        let mut guid = None;
+        let mut position = query_start;
+
+        while position < query_end {
+            let param_end = find_bytes(&buf[..query_end], b"&", position).unwrap_or(query_end);
+
+            let param = &buf[position..param_end];
+            let param = param.strip_prefix(b"amp;").unwrap_or(param);
+
+            if let Some(value) = param.strip_prefix(b"guid=") {
+                if is_guid(value) {
+                    let value_start = param_end - value.len();
+                    guid = Some(value_start..param_end);
+                }
+            }
+
+            position = param_end + 1;
+        }
+
+        Some(GuidMatch {
+            start,
+            guid: guid?,
+            end: query_end,
+            path: Some(path_start..path_end),
+            reference: None,
+        })
+    }
+
+    /// Length of a URL part up to one of the terminators, None if it never ends
+    fn url_part_len(buf: &[u8], start: usize, terminators: &[u8]) -> Option<usize> {
+        let part = buf.get(start..(start + MAX_URL_PART).min(buf.len()))?;
+
+        part.iter()
+            .position(|byte| terminators.contains(byte) || byte.is_ascii_whitespace())
+    }
+}
+
+/// Decodes percent encoded characters of a URL path
+pub fn url_decode(encoded: &[u8]) -> String {
+    let mut decoded = Vec::<u8>::with_capacity(encoded.len());
+    let mut index = 0;
+
+    while index < encoded.len() {
+        let hex = encoded
+            .get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match (encoded[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Percent encodes a path for use within a URL, slashes are kept as is
+pub fn url_encode(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~/".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+
+    encoded
+}
+
+/// What references to the null GUID are replaced with in text assets
+const NULL_REFERENCE: &[u8] = b"{fileID: 0}";
+
+/// Unity text assets can embed JSON in strings (ex. VFX graph properties), so both are scanned
+pub const TEXT_SCANNERS: &[&dyn ReferenceScanner] = &[&YamlScanner, &JsonScanner];
+
+/// Returns true if the given bytes are a valid GUID (32 hex digits)
+pub fn is_guid(bytes: &[u8]) -> bool {
+    bytes.len() == GUID_LENGTH && bytes.iter().all(u8::is_ascii_hexdigit)
+}
+
+/// Finds the first occurrence of `pattern` at or after `from`
+pub fn find_bytes(buf: &[u8], pattern: &[u8], from: usize) -> Option<usize> {
+    if from >= buf.len() {
+        return None;
+    }
+
+    buf[from..]
+        .windows(pattern.len())
+        .position(|window| window == pattern)
+        .map(|position| position + from)
+}
+
+/// Streams a file through scanners, every GUID found is passed to the resolver
+/// and replaced if it returns a new value
+///
+/// Memory use is bounded by the chunk size no matter how large the file is
+pub fn rewrite_stream<R, W, F>(
+    scanners: &[&dyn ReferenceScanner],
+    mut reader: R,
+    mut writer: W,
+    mut resolver: F,
+) -> io::Result<()>
+where
+    R: Read,
+    W: Write,
+    F: Resolver,
+{
+    let overlap = scanners
+        .iter()
+        .map(|scanner| scanner.max_match_len())
+        .max()
+        .unwrap_or_default();
+
+    let mut buf = Vec::<u8>::with_capacity(CHUNK_SIZE + overlap);
+    let mut chunk = vec![0u8; CHUNK_SIZE];
+
+    // Where scanning resumes within the buffer (a match may end in the kept tail)
+    let mut scan_from = 0usize;
+
+    loop {
+        let read = reader.read(&mut chunk)?;
+        let eof = read == 0;
+
+        buf.extend_from_slice(&chunk[..read]);
+
+        // Matches starting past this point may be cut off, they're handled next chunk
+        let limit = if eof {
+            buf.len()
+        } else {
+            buf.len().saturating_sub(overlap)
+        };
+
+        let mut written = 0usize;
+
+        // The next match of every scanner, only refreshed once scanning moves past it
+        let mut upcoming: Vec<Option<GuidMatch>> = scanners
+            .iter()
+            .map(|scanner| scanner.find(&buf, scan_from))
+            .collect();
+
+        while let Some(found) = upcoming
+            .iter()
+            .flatten()
+            .min_by_key(|next| next.start)
+            .cloned()
+        {
+            if found.start >= limit {
+                break;
+            }
+
+            // Scanners only hand out valid GUIDs, which are always ASCII
+            let guid = std::str::from_utf8(&buf[found.guid.clone()]).unwrap();
+
+            let replacement = resolver.resolve(guid);
+
+            // Paths always come before the GUID within a reference
+            if let Some(path) = &found.path {
+                let new_path = resolver
+                    .project_path(guid)
+                    .filter(|new_path| *new_path != url_decode(&buf[path.clone()]));
+
+                if let Some(new_path) = new_path {
+                    writer.write_all(&buf[written..path.start])?;
+                    writer.write_all(url_encode(&new_path).as_bytes())?;
+                    written = path.end;
+                }
+            }
+
+            match (replacement, &found.reference) {
+                // Unity writes references to nothing without a GUID
+                (Some(replacement), Some(reference)) if replacement == NULL_GUID => {
+                    writer.write_all(&buf[written..reference.start])?;
+                    writer.write_all(NULL_REFERENCE)?;
+                    written = reference.end;
+                }
+                (Some(replacement), _) => {
+                    writer.write_all(&buf[written..found.guid.start])?;
+                    writer.write_all(replacement.as_bytes())?;
+                    written = found.guid.end;
+                }
+                (None, _) => {}
+            }
+
+            scan_from = found.end;
+
+            for (scanner, next) in scanners.iter().zip(upcoming.iter_mut()) {
+                if next.as_ref().is_some_and(|next| next.start < scan_from) {
+                    *next = scanner.find(&buf, scan_from);
+                }
+            }
+        }
+
+        if eof {
+            writer.write_all(&buf[written..])?;
+            break;
+        }
+
+        // Nothing can start between scan_from and limit, so everything before both is done
+        let keep_from = limit.max(scan_from);
+
+        writer.write_all(&buf[written..keep_from])?;
+        buf.drain(..keep_from);
+
+        scan_from = 0;
+    }
+
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GUID: &str = "0123456789abcdef0123456789abcdef";
+    const REPLACEMENT: &str = "fedcba9876543210fedcba9876543210";
+
+    /// Hands out at most `step` bytes per read, so chunks end at awkward places
+    struct Trickle<'a> {
+        data: &'a [u8],
+        step: usize,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let count = self.step.min(buf.len()).min(self.data.len());
+            buf[..count].copy_from_slice(&self.data[..count]);
+            self.data = &self.data[count..];
+            Ok(count)
+        }
+    }
+
+    fn replace(guid: &str) -> Option<String> {
+        (guid == GUID).then(|| REPLACEMENT.to_string())
+    }
+
+    /// Rewrites a whole buffer at once, what streaming has to be identical to
+    fn rewrite_in_memory(scanners: &[&dyn ReferenceScanner], buf: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut written = 0;
+
+        // Each scanner's next match is kept around, rescanning for every match is quadratic
+        let mut upcoming: Vec<Option<GuidMatch>> = scanners
+            .iter()
+            .map(|scanner| scanner.find(buf, 0))
+            .collect();
+
+        while let Some(found) = upcoming
+            .iter()
+            .flatten()
+            .min_by_key(|found| found.start)
+            .cloned()
+        {
+            let guid = std::str::from_utf8(&buf[found.guid.clone()]).unwrap();
+
+            if let Some(replacement) = replace(guid) {
+                out.extend_from_slice(&buf[written..found.guid.start]);
+                out.extend_from_slice(replacement.as_bytes());
+                written = found.guid.end;
+            }
+
+            for (scanner, next) in scanners.iter().zip(upcoming.iter_mut()) {
+                if next.as_ref().is_some_and(|next| next.start < found.end) {
+                    *next = scanner.find(buf, found.end);
+                }
+            }
+        }
+
+        out.extend_from_slice(&buf[written..]);
+        out
+    }
+
+    fn rewrite_streamed(scanners: &[&dyn ReferenceScanner], buf: &[u8], step: usize) -> Vec<u8> {
+        let mut out = Vec::new();
+        rewrite_stream(scanners, Trickle { data: buf, step }, &mut out, replace).unwrap();
+        out
+    }
+
+    /// Places a reference so it starts at `offset`, padded on both sides
+    fn padded(reference: &str, offset: usize) -> Vec<u8> {
+        let mut buf = vec![b'x'; offset];
+        buf.extend_from_slice(reference.as_bytes());
+        buf.extend(std::iter::repeat_n(b'y', CHUNK_SIZE));
+        buf
+    }
+
+    #[test]
+    fn yaml_reference_straddling_a_chunk_is_rewritten() {
+        let reference = format!("m_Texture: {{fileID: 2800000, guid: {}, type: 3}}", GUID);
+
+        for offset in CHUNK_SIZE - reference.len() - 4..CHUNK_SIZE + 4 {
+            let buf = padded(&reference, offset);
+            let expected = rewrite_in_memory(TEXT_SCANNERS, &buf);
+
+            assert_ne!(expected, buf);
+            assert_eq!(rewrite_streamed(TEXT_SCANNERS, &buf, CHUNK_SIZE), expected);
+        }
+    }
+
+    #[test]
+    fn short_reads_match_the_in_memory_rewrite() {
+        let mut buf = Vec::new();
+
+        for index in 0..4000 {
+            buf.extend_from_slice(
+                format!("- {{fileID: {}, guid: {}, type: 2}}\n", index, GUID).as_bytes(),
+            );
+        }
+
+        let expected = rewrite_in_memory(TEXT_SCANNERS, &buf);
+
+        for step in [1, 7, 4096, CHUNK_SIZE - 1, CHUNK_SIZE] {
+            assert_eq!(rewrite_streamed(TEXT_SCANNERS, &buf, step), expected);
+        }
+    }
+
+    #[test]
+    fn unknown_guids_are_left_alone() {
+        let buf = padded("guid: 00000000000000000000000000000001", CHUNK_SIZE - 10);
+        assert_eq!(rewrite_streamed(TEXT_SCANNERS, &buf, CHUNK_SIZE), buf);
+    }
+
+    #[test]
+    fn escaped_json_references_are_found() {
+        let cases = [
+            format!("{{\"guid\": \"{}\"}}", GUID),
+            format!("\"{{\\\"guid\\\":\\\"{}\\\"}}\"", GUID),
+            format!("\"{{\\\\\\\"guid\\\\\\\" : \\\\\\\"{}\\\\\\\"}}\"", GUID),
+        ];
+
+        for case in cases {
+            let rewritten = rewrite_streamed(&[&JsonScanner], case.as_bytes(), CHUNK_SIZE);
+            assert_eq!(
+                String::from_utf8(rewritten).unwrap(),
+                case.replace(GUID, REPLACEMENT)
+            );
+        }
+    }
+
+    #[test]
+    fn json_keys_must_be_quoted() {
+        let case = format!("{{\"m_guid\": \"{}\"}}", GUID);
+        let rewritten = rewrite_streamed(&[&JsonScanner], case.as_bytes(), CHUNK_SIZE);
+
+        assert_eq!(rewritten, case.as_bytes());
+    }
+
+    #[test]
+    fn json_reference_straddling_a_chunk_is_rewritten() {
+        let reference = format!("\\\\\\\"guid\\\\\\\": \\\\\\\"{}\\\\\\\"", GUID);
+
+        for offset in CHUNK_SIZE - reference.len() - 4..CHUNK_SIZE + 4 {
+            let buf = padded(&reference, offset);
+            let expected = rewrite_in_memory(&[&JsonScanner], &buf);
+
+            assert_ne!(expected, buf);
+            assert_eq!(
+                rewrite_streamed(&[&JsonScanner], &buf, CHUNK_SIZE),
+                expected
+            );
+        }
+    }
+
+    fn null_out(_guid: &str) -> Option<String> {
+        Some(NULL_GUID.to_string())
+    }
+
+    #[test]
+    fn nulled_references_become_empty_references() {
+        let cases = [
+            (
+                format!(
+                    "  m_Texture: {{fileID: 2800000, guid: {}, type: 3}}\n",
+                    GUID
+                ),
+                "  m_Texture: {fileID: 0}\n".to_string(),
+            ),
+            (
+                format!("  - {{fileID: 2100000, guid: {},\n      type: 2}}\n", GUID),
+                "  - {fileID: 0}\n".to_string(),
+            ),
+            // Not a flow mapping, only the GUID can change
+            (
+                format!("guid: {}\n", GUID),
+                format!("guid: {}\n", NULL_GUID),
+            ),
+        ];
+
+        for (case, expected) in cases {
+            let mut out = Vec::new();
+            rewrite_stream(TEXT_SCANNERS, case.as_bytes(), &mut out, null_out).unwrap();
+
+            assert_eq!(String::from_utf8(out).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn nulled_reference_straddling_a_chunk_is_rewritten() {
+        let reference = format!("m_Texture: {{fileID: 2800000, guid: {}, type: 3}}", GUID);
+
+        for offset in CHUNK_SIZE - reference.len() - 4..CHUNK_SIZE + 4 {
+            let buf = padded(&reference, offset);
+
+            let mut out = Vec::new();
+            let reader = Trickle {
+                data: &buf,
+                step: CHUNK_SIZE,
+            };
+
+            rewrite_stream(TEXT_SCANNERS, reader, &mut out, null_out).unwrap();
+
+            let expected = String::from_utf8(buf.clone())
+                .unwrap()
+                .replace(&reference, "m_Texture: {fileID: 0}");
+
+            assert_eq!(String::from_utf8(out).unwrap(), expected);
+        }
+    }
+}
@@ -0,0 +1,129 @@
+// ===================================================================================
+//  BSD 3-Clause License
+//
+//  Copyright (c) 2023-2024, Liam R. (zCubed3)
+//
+//  Redistribution and use in source and binary forms, with or without
+//  modification, are permitted provided that the following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this
+//     list of conditions and the following disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice,
+//     this list of conditions and the following disclaimer in the documentation
+//     and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its
+//     contributors may be used to endorse or promote products derived from
+//     this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+//  AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+//  IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+//  FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+//  DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+//  CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+//  OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+//  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ===================================================================================
+
+use std::path::{Component, Path, PathBuf};
+
+use crate::meta_file::MetaFile;
+use crate::packages::{Package, PackageIndex};
+use crate::relocation::Relocator;
+
+/// A folder assets are migrated from, either the assets folder or an embedded package
+#[derive(Debug, Clone)]
+pub struct SourceRoot {
+    /// The folder itself
+    pub path: PathBuf,
+
+    /// Where its assets are exported to, relative to the export path
+    pub output: PathBuf,
+
+    /// The project path of the folder (ex. Assets or Packages/com.company.package)
+    pub project_path: String,
+
+    /// Whether relocation rules apply, those are written relative to the assets folder
+    pub relocate: bool,
+}
+
+/// Returns the roots of a project, its assets folder followed by its embedded packages
+pub fn source_roots(assets: &str, packages: &PackageIndex) -> Vec<SourceRoot> {
+    let mut roots = vec![SourceRoot::assets(assets)];
+
+    for package in packages.packages.iter().filter(|p| p.embedded) {
+        roots.push(SourceRoot::package(package));
+    }
+
+    roots
+}
+
+/// Returns the project path of an asset within the first root containing it
+pub fn project_path_in(roots: &[SourceRoot], meta: &MetaFile) -> Option<String> {
+    let directory = Path::new(&meta.directory);
+
+    roots.iter().find_map(|root| {
+        let relative = root.relative(directory)?;
+        Some(root.project_path_of(&relative.join(&meta.base_name)))
+    })
+}
+
+impl SourceRoot {
+    pub fn assets(path: &str) -> Self {
+        Self {
+            path: PathBuf::from(path),
+            output: PathBuf::new(),
+            project_path: String::from("Assets"),
+            relocate: true,
+        }
+    }
+
+    /// Assets of embedded packages are exported to Packages/<folder> next to the others
+    pub fn package(package: &Package) -> Self {
+        let folder = package.path.file_name().unwrap_or_default();
+
+        Self {
+            path: package.path.clone(),
+            output: Path::new("Packages").join(folder),
+            project_path: format!("Packages/{}", package.name),
+            relocate: false,
+        }
+    }
+
+    /// Returns the path of a directory relative to this root, None if it's outside of it
+    pub fn relative<'a>(&self, directory: &'a Path) -> Option<&'a Path> {
+        directory.strip_prefix(&self.path).ok()
+    }
+
+    /// Returns where a directory within this root is exported to
+    pub fn export_dir(&self, export_path: &str, relocator: &Relocator, relative: &Path) -> PathBuf {
+        let mut export_dir = PathBuf::from(export_path);
+        export_dir.push(&self.output);
+
+        if self.relocate {
+            export_dir.push(relocator.relocate(relative));
+        } else {
+            export_dir.push(relative);
+        }
+
+        export_dir
+    }
+
+    /// Returns the project path of a path relative to this root (ex. Assets/Icon.png)
+    pub fn project_path_of(&self, relative: &Path) -> String {
+        let mut project_path = self.project_path.clone();
+
+        for component in relative.components() {
+            if let Component::Normal(part) = component {
+                project_path.push('/');
+                project_path.push_str(&part.to_string_lossy());
+            }
+        }
+
+        project_path
+    }
+}
@@ -0,0 +1,228 @@
+// ===================================================================================
+//  BSD 3-Clause License
+//
+//  Copyright (c) 2023-2024, Liam R. (zCubed3)
+//
+//  Redistribution and use in source and binary forms, with or without
+//  modification, are permitted provided that the following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this
+//     list of conditions and the following disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice,
+//     this list of conditions and the following disclaimer in the documentation
+//     and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its
+//     contributors may be used to endorse or promote products derived from
+//     this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+//  AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+//  IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+//  FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+//  DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+//  CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+//  OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+//  OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ===================================================================================
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+
+/// Work stealing queue shared between a fixed number of workers
+///
+/// Each worker owns a local queue it pushes to and pops from, idle workers steal
+/// from the front of everyone else's queue. Items may be pushed while work is
+/// in progress, the queue is only drained once every popped item was finished
+///
+/// Workers hold a [`Finishing`] guard while working on an item, if a worker panics
+/// the queue is abandoned so the other workers return and the panic reaches `join`
+pub struct WorkQueue<T> {
+    queues: Vec<Mutex<VecDeque<T>>>,
+
+    /// Items that are either queued or being worked on
+    pending: AtomicUsize,
+
+    /// Set once a worker panicked, nobody pops anything afterwards
+    abandoned: AtomicBool,
+
+    idle_lock: Mutex<()>,
+    idle_condvar: Condvar,
+}
+
+impl<T> WorkQueue<T> {
+    pub fn new(workers: usize, initial: Vec<T>) -> Self {
+        let workers = workers.max(1);
+        let mut queues: Vec<VecDeque<T>> = (0..workers).map(|_| VecDeque::new()).collect();
+
+        let pending = initial.len();
+
+        for (index, item) in initial.into_iter().enumerate() {
+            queues[index % workers].push_back(item);
+        }
+
+        Self {
+            queues: queues.into_iter().map(Mutex::new).collect(),
+            pending: AtomicUsize::new(pending),
+            abandoned: AtomicBool::new(false),
+            idle_lock: Mutex::new(()),
+            idle_condvar: Condvar::new(),
+        }
+    }
+
+    pub fn worker_count(&self) -> usize {
+        self.queues.len()
+    }
+
+    /// Queues more work on a worker's local queue
+    pub fn push(&self, worker: usize, item: T) {
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        self.queues[worker].lock().unwrap().push_back(item);
+
+        let _lock = self.idle_lock.lock().unwrap();
+        self.idle_condvar.notify_one();
+    }
+
+    /// Blocks until work is available, returns None once the queue is drained
+    /// or abandoned
+    pub fn pop(&self, worker: usize) -> Option<T> {
+        loop {
+            if self.is_abandoned() {
+                return None;
+            }
+
+            if let Some(item) = self.try_pop(worker) {
+                return Some(item);
+            }
+
+            let lock = self.idle_lock.lock().unwrap();
+
+            // Checked again while locked, pushes and finishes notify under this lock
+            if let Some(item) = self.try_pop(worker) {
+                return Some(item);
+            }
+
+            if self.is_drained() || self.is_abandoned() {
+                return None;
+            }
+
+            let _lock = self.idle_condvar.wait(lock).unwrap();
+        }
+    }
+
+    /// Returns a guard that marks a popped item as done once dropped, including
+    /// while unwinding out of a panicking worker
+    pub fn finishing(&self) -> Finishing<'_, T> {
+        Finishing(self)
+    }
+
+    pub fn is_drained(&self) -> bool {
+        self.pending.load(Ordering::SeqCst) == 0
+    }
+
+    pub fn is_abandoned(&self) -> bool {
+        self.abandoned.load(Ordering::SeqCst)
+    }
+
+    fn finish(&self, panicked: bool) {
+        if panicked {
+            self.abandoned.store(true, Ordering::SeqCst);
+        }
+
+        let drained = self.pending.fetch_sub(1, Ordering::SeqCst) == 1;
+
+        if drained || panicked {
+            // A poisoned lock still has to wake everyone, the guard is never poisoned
+            let _lock = self.idle_lock.lock().unwrap_or_else(|err| err.into_inner());
+            self.idle_condvar.notify_all();
+        }
+    }
+
+    fn try_pop(&self, worker: usize) -> Option<T> {
+        if let Some(item) = self.queues[worker].lock().unwrap().pop_back() {
+            return Some(item);
+        }
+
+        let count = self.queues.len();
+
+        for offset in 1..count {
+            let victim = (worker + offset) % count;
+
+            if let Some(item) = self.queues[victim].lock().unwrap().pop_front() {
+                return Some(item);
+            }
+        }
+
+        None
+    }
+}
+
+/// Finishes a popped item when dropped, see [`WorkQueue::finishing`]
+pub struct Finishing<'a, T>(&'a WorkQueue<T>);
+
+impl<T> Drop for Finishing<'_, T> {
+    fn drop(&mut self) {
+        self.0.finish(thread::panicking());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread::scope;
+
+    #[test]
+    fn panicking_workers_reach_join() {
+        let queue = WorkQueue::new(4, (0..64).collect());
+
+        let result = std::panic::catch_unwind(|| {
+            scope(|scope| {
+                for worker in 0..queue.worker_count() {
+                    let queue = &queue;
+
+                    scope.spawn(move || {
+                        while let Some(item) = queue.pop(worker) {
+                            let _finishing = queue.finishing();
+                            assert_ne!(item, 13, "bad item");
+                        }
+                    });
+                }
+            });
+        });
+
+        assert!(result.is_err());
+        assert!(queue.is_abandoned());
+    }
+
+    #[test]
+    fn pushed_items_are_all_popped() {
+        let queue = WorkQueue::new(3, vec![0u32]);
+        let popped = AtomicUsize::new(0);
+
+        scope(|scope| {
+            for worker in 0..queue.worker_count() {
+                let (queue, popped) = (&queue, &popped);
+
+                scope.spawn(move || {
+                    while let Some(depth) = queue.pop(worker) {
+                        let _finishing = queue.finishing();
+                        popped.fetch_add(1, Ordering::SeqCst);
+
+                        if depth < 8 {
+                            queue.push(worker, depth + 1);
+                            queue.push(worker, depth + 1);
+                        }
+                    }
+                });
+            }
+        });
+
+        assert_eq!(popped.load(Ordering::SeqCst), (1 << 9) - 1);
+        assert!(queue.is_drained());
+    }
+}